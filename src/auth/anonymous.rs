@@ -1,14 +1,25 @@
+use std::sync::Arc;
+
 use url::Url;
 
-use super::Authenticator;
+use super::{Authenticator, Endpoints, Scope};
 
 #[derive(Clone, Debug, Default)]
-pub struct Auth;
+pub struct Auth {
+    endpoints: Arc<Endpoints>,
+}
 
 impl Auth {
     #[must_use]
-    pub const fn new() -> Self {
-        Self {}
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Points this [`Auth`] at `endpoints` instead of Reddit's.
+    #[must_use]
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.set_endpoints(endpoints);
+        self
     }
 }
 
@@ -27,7 +38,16 @@ impl Authenticator for Auth {
         Ok(req)
     }
 
-    fn base_url(&self) -> url::Url {
-        Url::parse("https://api.reddit.com/").expect("this to be a valid url")
+    /// Reddit only lets anonymous users read public content.
+    fn has_scope(&self, scope: Scope) -> bool {
+        matches!(scope, Scope::Read | Scope::WikiRead | Scope::History)
+    }
+
+    fn set_endpoints(&mut self, endpoints: Endpoints) {
+        self.endpoints = Arc::new(endpoints);
+    }
+
+    fn base_url(&self) -> Url {
+        self.endpoints.api.clone()
     }
 }
@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use url::Url;
+
+use super::{Anon, Application, Authenticator, Code, Endpoints, Password, Result, Scope, Token};
+
+/// An [`Authenticator`] picked at runtime, e.g. from a config file.
+///
+/// A `Client<AnyAuth>` is a single type whichever way it logged in, so it can be passed
+/// around without making everything generic over the [`Authenticator`].
+///
+/// # Example
+/// ```no_run
+/// use reddit_api::{AnyAuth, Application, Client};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), reddit_api::Error> {
+/// let auth: AnyAuth = match std::env::var("REDDIT_CLIENT_ID") {
+///     Ok(id) => Application::new(id, "secret".to_string()).into(),
+///     Err(_) => AnyAuth::default(),
+/// };
+///
+/// let client = Client::new("my-bot (by u/username)").login(auth).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub enum AnyAuth {
+    Anon(Anon),
+    Password(Password),
+    Application(Application),
+    Code(Code),
+}
+
+/// Evaluates `$call` with `$auth` bound to whichever [`Authenticator`] is inside.
+macro_rules! dispatch {
+    ($self:expr, $auth:ident => $call:expr) => {
+        match $self {
+            AnyAuth::Anon($auth) => $call,
+            AnyAuth::Password($auth) => $call,
+            AnyAuth::Application($auth) => $call,
+            AnyAuth::Code($auth) => $call,
+        }
+    };
+}
+
+impl Default for AnyAuth {
+    fn default() -> Self {
+        Self::Anon(Anon::new())
+    }
+}
+
+impl Authenticator for AnyAuth {
+    async fn login(&mut self, client: &reqwest::Client) -> Result<()> {
+        dispatch!(self, auth => auth.login(client).await)
+    }
+
+    async fn logout(&mut self, client: &reqwest::Client) -> Result<()> {
+        dispatch!(self, auth => auth.logout(client).await)
+    }
+
+    fn auth_request(&self, req: reqwest::RequestBuilder) -> Result<reqwest::RequestBuilder> {
+        dispatch!(self, auth => auth.auth_request(req))
+    }
+
+    fn needs_refresh(&self, margin: Duration) -> bool {
+        dispatch!(self, auth => auth.needs_refresh(margin))
+    }
+
+    fn has_scope(&self, scope: Scope) -> bool {
+        dispatch!(self, auth => auth.has_scope(scope))
+    }
+
+    fn token(&self) -> Option<&Token> {
+        dispatch!(self, auth => auth.token())
+    }
+
+    fn restore(&mut self, token: Token) {
+        dispatch!(self, auth => auth.restore(token));
+    }
+
+    fn set_endpoints(&mut self, endpoints: Endpoints) {
+        dispatch!(self, auth => auth.set_endpoints(endpoints));
+    }
+
+    fn base_url(&self) -> Url {
+        dispatch!(self, auth => auth.base_url())
+    }
+}
+
+impl From<Anon> for AnyAuth {
+    fn from(auth: Anon) -> Self {
+        Self::Anon(auth)
+    }
+}
+
+impl From<Password> for AnyAuth {
+    fn from(auth: Password) -> Self {
+        Self::Password(auth)
+    }
+}
+
+impl From<Application> for AnyAuth {
+    fn from(auth: Application) -> Self {
+        Self::Application(auth)
+    }
+}
+
+impl From<Code> for AnyAuth {
+    fn from(auth: Code) -> Self {
+        Self::Code(auth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnyAuth;
+    use crate::auth::{Anon, Authenticator, Password, Scope};
+
+    #[test]
+    fn test_dispatch() {
+        let anon = AnyAuth::from(Anon::new());
+        assert_eq!(anon.base_url().as_str(), "https://api.reddit.com/");
+        assert!(!anon.has_scope(Scope::Submit));
+        assert!(!anon.needs_refresh(std::time::Duration::ZERO));
+
+        let password = AnyAuth::from(Password::new("id", "secret", "user", "password"));
+        assert_eq!(password.base_url().as_str(), "https://oauth.reddit.com/");
+        assert!(!password.needs_refresh(std::time::Duration::ZERO));
+    }
+}
@@ -0,0 +1,182 @@
+use std::{sync::Arc, time::Duration};
+
+use url::Url;
+
+use crate::auth::{
+    request_token, revoke_token, Authenticator, Endpoints, Error, Scope, Scopes, Token, TokenState,
+};
+
+/// The `device_id` Reddit asks installed apps to send when they don't track devices.
+pub const DO_NOT_TRACK: &str = "DO_NOT_TRACK_THIS_DEVICE";
+
+/// The grant used to request an application-only token.
+#[derive(Clone)]
+enum Grant {
+    /// `client_credentials`, for confidential clients (web and script apps).
+    ClientCredentials { client_secret: Arc<str> },
+    /// `installed_client`, for installed apps, which have no secret.
+    InstalledClient { device_id: Arc<str> },
+}
+
+/// Application-only OAuth, which authenticates the app itself rather than a user.
+///
+/// This grants access to `oauth.reddit.com` without a Reddit account, but only to
+/// endpoints that don't need a user context.
+#[derive(Clone)]
+pub struct Auth {
+    client_id: Arc<str>,
+    grant: Grant,
+    scopes: Option<Scopes>,
+
+    state: TokenState,
+}
+
+impl Authenticator for Auth {
+    fn auth_request(&self, req: reqwest::RequestBuilder) -> super::Result<reqwest::RequestBuilder> {
+        self.state.auth_request(req)
+    }
+
+    fn needs_refresh(&self, margin: Duration) -> bool {
+        self.state.needs_refresh(margin)
+    }
+
+    fn token(&self) -> Option<&Token> {
+        self.state.token.as_ref()
+    }
+
+    fn set_endpoints(&mut self, endpoints: Endpoints) {
+        self.state.set_endpoints(endpoints);
+    }
+
+    fn base_url(&self) -> Url {
+        self.state.base_url()
+    }
+
+    fn restore(&mut self, token: Token) {
+        self.state.token = Some(token);
+    }
+
+    async fn login(&mut self, client: &reqwest::Client) -> super::Result<()> {
+        let mut form = match self.grant {
+            Grant::ClientCredentials { .. } => vec![("grant_type", "client_credentials")],
+            Grant::InstalledClient { ref device_id } => vec![
+                (
+                    "grant_type",
+                    "https://oauth.reddit.com/grants/installed_client",
+                ),
+                ("device_id", device_id),
+            ],
+        };
+
+        let scope = self.scopes.as_ref().map(Scope::join);
+        if let Some(ref scope) = scope {
+            form.push(("scope", scope));
+        }
+
+        let token = request_token(
+            client,
+            &self.state.endpoints,
+            &self.client_id,
+            self.client_secret(),
+            &form,
+        )
+        .await?;
+        self.state.token = Some(token);
+
+        Ok(())
+    }
+
+    async fn logout(&mut self, client: &reqwest::Client) -> super::Result<()> {
+        match self.state.token {
+            None => Err(Error::LoggedOut),
+            Some(ref token) => {
+                revoke_token(
+                    client,
+                    &self.state.endpoints,
+                    &self.client_id,
+                    self.client_secret(),
+                    token.as_str(),
+                    "access_token",
+                )
+                .await?;
+
+                self.state.token = None;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Auth {
+    /// Creates a new [`Auth`] for a confidential client, using the `client_credentials` grant.
+    pub fn new<S: Into<Arc<str>>>(client_id: S, client_secret: S) -> Self {
+        Self {
+            client_id: client_id.into(),
+            grant: Grant::ClientCredentials {
+                client_secret: client_secret.into(),
+            },
+            scopes: None,
+            state: TokenState::default(),
+        }
+    }
+
+    /// Creates a new [`Auth`] for an installed app, using the `installed_client` grant.
+    ///
+    /// `device_id` must be a unique, 20-30 character long, identifier per device.
+    /// Use [`DO_NOT_TRACK`] if you don't want to track devices.
+    pub fn installed<S: Into<Arc<str>>>(client_id: S, device_id: S) -> Self {
+        Self {
+            client_id: client_id.into(),
+            grant: Grant::InstalledClient {
+                device_id: device_id.into(),
+            },
+            scopes: None,
+            state: TokenState::default(),
+        }
+    }
+
+    /// Points this [`Auth`] at `endpoints` instead of Reddit's.
+    #[must_use]
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.set_endpoints(endpoints);
+        self
+    }
+
+    /// Sets the scopes to request, instead of every scope the app can access.
+    #[must_use]
+    pub fn scopes<I: IntoIterator<Item = Scope>>(mut self, scopes: I) -> Self {
+        self.scopes = Some(scopes.into_iter().collect());
+        self
+    }
+
+    /// Installed apps have no secret, so they use an empty one.
+    fn client_secret(&self) -> &str {
+        match self.grant {
+            Grant::ClientCredentials { ref client_secret } => client_secret,
+            Grant::InstalledClient { .. } => "",
+        }
+    }
+}
+
+impl std::fmt::Debug for Auth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Auth")
+            .field("client_id", &self.client_id)
+            .field(
+                "grant",
+                match self.grant {
+                    Grant::ClientCredentials { .. } => &"client_credentials",
+                    Grant::InstalledClient { .. } => &"installed_client",
+                },
+            )
+            .field(
+                "token",
+                if self.state.token.is_none() {
+                    &"not logged in"
+                } else {
+                    &"[redacted]"
+                },
+            )
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,315 @@
+use std::{sync::Arc, time::Duration};
+
+use url::Url;
+
+use crate::auth::{
+    request_token, revoke_token, Authenticator, Endpoints, Error, Scope, Scopes, Token, TokenState,
+};
+
+/// Authorization code OAuth, where a user grants your app access to their account.
+///
+/// The flow goes like this:
+///  1. Send the user to [`Auth::authorize_url`].
+///  2. Reddit redirects them to your `redirect_uri` with a `code` and the `state` you sent.
+///  3. Pass the code to [`Auth::with_code`] and log in with it. Logging in exchanges the code
+///     for an access token and a permanent refresh token.
+///
+/// Once logged in, save [`Auth::refresh_token`] and use [`Auth::with_refresh_token`] to resume
+/// the session later without asking the user again.
+#[derive(Clone)]
+pub struct Auth {
+    client_id: Arc<str>,
+    client_secret: Arc<str>,
+    redirect_uri: Url,
+    scopes: Scopes,
+
+    code: Option<Arc<str>>,
+    refresh_token: Option<Arc<str>>,
+    state: TokenState,
+}
+
+impl Authenticator for Auth {
+    fn auth_request(&self, req: reqwest::RequestBuilder) -> super::Result<reqwest::RequestBuilder> {
+        self.state.auth_request(req)
+    }
+
+    fn needs_refresh(&self, margin: Duration) -> bool {
+        self.state.needs_refresh(margin)
+    }
+
+    fn token(&self) -> Option<&Token> {
+        self.state.token.as_ref()
+    }
+
+    fn set_endpoints(&mut self, endpoints: Endpoints) {
+        self.state.set_endpoints(endpoints);
+    }
+
+    fn base_url(&self) -> Url {
+        self.state.base_url()
+    }
+
+    fn restore(&mut self, token: Token) {
+        if let Some(refresh_token) = token.refresh_token() {
+            self.refresh_token = Some(Arc::from(refresh_token));
+        }
+        self.state.token = Some(token);
+    }
+
+    /// Exchanges the authorization code if there is one, otherwise uses the refresh token.
+    async fn login(&mut self, client: &reqwest::Client) -> super::Result<()> {
+        let token = if let Some(code) = self.code.take() {
+            let form = [
+                ("grant_type", "authorization_code"),
+                ("code", &code),
+                ("redirect_uri", self.redirect_uri.as_str()),
+            ];
+
+            request_token(
+                client,
+                &self.state.endpoints,
+                &self.client_id,
+                &self.client_secret,
+                &form,
+            )
+            .await?
+        } else if let Some(ref refresh_token) = self.refresh_token {
+            let form = [
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token),
+            ];
+
+            request_token(
+                client,
+                &self.state.endpoints,
+                &self.client_id,
+                &self.client_secret,
+                &form,
+            )
+            .await?
+        } else {
+            return Err(Error::NoRefreshToken);
+        };
+
+        // Reddit only sends a refresh token when exchanging the code.
+        if let Some(refresh_token) = token.refresh_token() {
+            self.refresh_token = Some(Arc::from(refresh_token));
+        }
+        self.state.token = Some(token);
+
+        Ok(())
+    }
+
+    /// Revokes the refresh token, which also revokes every access token issued with it.
+    async fn logout(&mut self, client: &reqwest::Client) -> super::Result<()> {
+        match (&self.refresh_token, &self.state.token) {
+            (None, None) => return Err(Error::LoggedOut),
+            (Some(refresh_token), _) => {
+                revoke_token(
+                    client,
+                    &self.state.endpoints,
+                    &self.client_id,
+                    &self.client_secret,
+                    refresh_token,
+                    "refresh_token",
+                )
+                .await?;
+            }
+            (None, Some(token)) => {
+                revoke_token(
+                    client,
+                    &self.state.endpoints,
+                    &self.client_id,
+                    &self.client_secret,
+                    token.as_str(),
+                    "access_token",
+                )
+                .await?;
+            }
+        }
+
+        self.refresh_token = None;
+        self.state.token = None;
+        Ok(())
+    }
+}
+
+impl Auth {
+    /// Creates a new [`Auth`].
+    ///
+    /// `redirect_uri` must match the one registered for your app.
+    /// Installed apps have no secret, so they should use an empty `client_secret`.
+    pub fn new<S: Into<Arc<str>>>(client_id: S, client_secret: S, redirect_uri: Url) -> Self {
+        Self {
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            redirect_uri,
+            scopes: Scopes::from([Scope::All]),
+            code: None,
+            refresh_token: None,
+            state: TokenState::default(),
+        }
+    }
+
+    /// Points this [`Auth`] at `endpoints` instead of Reddit's.
+    #[must_use]
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.set_endpoints(endpoints);
+        self
+    }
+
+    /// Sets the scopes to request in [`Auth::authorize_url`].
+    ///
+    /// Defaults to [`Scope::All`].
+    #[must_use]
+    pub fn scopes<I: IntoIterator<Item = Scope>>(mut self, scopes: I) -> Self {
+        self.scopes = scopes.into_iter().collect();
+        self
+    }
+
+    /// Sets the authorization code Reddit redirected the user back with.
+    #[must_use]
+    pub fn with_code<S: Into<Arc<str>>>(mut self, code: S) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Sets a refresh token obtained from a previous session.
+    #[must_use]
+    pub fn with_refresh_token<S: Into<Arc<str>>>(mut self, refresh_token: S) -> Self {
+        self.refresh_token = Some(refresh_token.into());
+        self
+    }
+
+    /// Returns the refresh token, if we have one.
+    #[must_use]
+    pub fn refresh_token(&self) -> Option<&str> {
+        self.refresh_token.as_deref()
+    }
+
+    /// Returns the [`Url`] you should send the user to, so they can authorize your app.
+    ///
+    /// `state` should be a unique, random, string. Reddit sends it back along with the code,
+    /// and you should check it matches.
+    #[must_use]
+    pub fn authorize_url(&self, state: &str) -> Url {
+        let mut url = self.state.endpoints.authorize.clone();
+
+        url.query_pairs_mut()
+            .append_pair("client_id", &self.client_id)
+            .append_pair("response_type", "code")
+            .append_pair("state", state)
+            .append_pair("redirect_uri", self.redirect_uri.as_str())
+            .append_pair("duration", "permanent")
+            .append_pair("scope", &Scope::join(&self.scopes));
+
+        url
+    }
+
+    /// Waits for Reddit to redirect the user back to `redirect_uri`, and returns the
+    /// authorization code.
+    ///
+    /// This binds a listener on the host and port of `redirect_uri`, so it should be a
+    /// loopback address like `http://127.0.0.1:65010/authorize_callback`.
+    ///
+    /// # Errors
+    /// Returns `Err` if we can't listen on `redirect_uri`, the user denied access or the
+    /// `state` doesn't match.
+    #[cfg(feature = "loopback")]
+    #[doc(cfg(feature = "loopback"))]
+    pub async fn listen_for_code(&self, state: &str) -> super::Result<String> {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        use tokio::net::TcpListener;
+
+        let host = self.redirect_uri.host_str().unwrap_or("127.0.0.1");
+        let port = self.redirect_uri.port_or_known_default().unwrap_or(80);
+        let listener = TcpListener::bind((host, port)).await?;
+
+        loop {
+            let (mut stream, _) = listener.accept().await?;
+            let mut line = String::new();
+            BufReader::new(&mut stream).read_line(&mut line).await?;
+
+            // GET /authorize_callback?state=...&code=... HTTP/1.1
+            let Some(target) = line.split_whitespace().nth(1) else {
+                continue;
+            };
+            let Ok(url) = self.redirect_uri.join(target) else {
+                continue;
+            };
+
+            let mut code = None;
+            let mut error = None;
+            let mut got_state = None;
+            for (k, v) in url.query_pairs() {
+                match k.as_ref() {
+                    "code" => code = Some(v.into_owned()),
+                    "error" => error = Some(v.into_owned()),
+                    "state" => got_state = Some(v.into_owned()),
+                    _ => {}
+                }
+            }
+
+            // probably the browser asking for a favicon.
+            if code.is_none() && error.is_none() {
+                continue;
+            }
+
+            stream
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nConnection: close\r\n\r\nYou may now close this window.",
+                )
+                .await?;
+
+            if got_state.as_deref() != Some(state) {
+                return Err(Error::StateMismatch);
+            }
+
+            if let Some(error) = error {
+                return Err(Error::Token(error));
+            }
+
+            if let Some(code) = code {
+                return Ok(code);
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Auth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Auth")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"[redacted]")
+            .field("redirect_uri", &self.redirect_uri)
+            .field("scopes", &self.scopes)
+            .field(
+                "token",
+                if self.state.token.is_none() {
+                    &"not logged in"
+                } else {
+                    &"[redacted]"
+                },
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::Auth;
+    use crate::auth::Scope;
+
+    #[test]
+    fn test_authorize_url() {
+        let redirect = Url::parse("http://127.0.0.1:65010/authorize_callback").unwrap();
+        let auth = Auth::new("id", "secret", redirect).scopes([Scope::Identity, Scope::Read]);
+
+        assert_eq!(
+            auth.authorize_url("xyz").as_str(),
+            "https://www.reddit.com/api/v1/authorize?client_id=id&response_type=code&state=xyz&redirect_uri=http%3A%2F%2F127.0.0.1%3A65010%2Fauthorize_callback&duration=permanent&scope=identity+read"
+        );
+    }
+}
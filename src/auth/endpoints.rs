@@ -0,0 +1,75 @@
+use url::Url;
+
+/// The URLs an [`crate::Authenticator`] talks to.
+///
+/// Defaults to Reddit's, but can be pointed somewhere else, like a proxy or a local server
+/// for tests.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Endpoints {
+    /// Where users are sent to authorize an app.
+    pub(crate) authorize: Url,
+    /// Where tokens are requested.
+    pub(crate) token: Url,
+    /// Where tokens are revoked.
+    pub(crate) revoke: Url,
+    /// The base URL of the API when authenticated.
+    pub(crate) oauth: Url,
+    /// The base URL of the API when anonymous.
+    pub(crate) api: Url,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        let parse = |url| Url::parse(url).expect("this to be a valid url");
+
+        Self {
+            authorize: parse("https://www.reddit.com/api/v1/authorize"),
+            token: parse("https://www.reddit.com/api/v1/access_token"),
+            revoke: parse("https://www.reddit.com/api/v1/revoke_token"),
+            oauth: parse("https://oauth.reddit.com/"),
+            api: parse("https://api.reddit.com/"),
+        }
+    }
+}
+
+impl Endpoints {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the URL users are sent to, to authorize an app.
+    #[must_use]
+    pub fn authorize(mut self, url: Url) -> Self {
+        self.authorize = url;
+        self
+    }
+
+    /// Sets the URL tokens are requested from.
+    #[must_use]
+    pub fn token(mut self, url: Url) -> Self {
+        self.token = url;
+        self
+    }
+
+    /// Sets the URL tokens are revoked at.
+    #[must_use]
+    pub fn revoke(mut self, url: Url) -> Self {
+        self.revoke = url;
+        self
+    }
+
+    /// Sets the base URL of the API for authenticated requests.
+    #[must_use]
+    pub fn oauth(mut self, url: Url) -> Self {
+        self.oauth = url;
+        self
+    }
+
+    /// Sets the base URL of the API for anonymous requests.
+    #[must_use]
+    pub fn api(mut self, url: Url) -> Self {
+        self.api = url;
+        self
+    }
+}
@@ -1,14 +1,42 @@
 mod anonymous;
+mod any;
+mod application;
+mod code;
+mod endpoints;
 mod password;
+mod scope;
+mod store;
+#[cfg(feature = "totp")]
+mod totp;
 use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use url::Url;
 
+use crate::Timestamp;
+
 /// Password based [`Authenticator`].
 pub type Password = self::password::Auth;
 /// Anonymous [`Authenticator`].
 pub type Anon = self::anonymous::Auth;
+/// Application-only (userless) [`Authenticator`].
+pub type Application = self::application::Auth;
+/// Authorization code based [`Authenticator`].
+pub type Code = self::code::Auth;
+
+pub use self::any::AnyAuth;
+pub use self::application::DO_NOT_TRACK;
+pub use self::endpoints::Endpoints;
+pub use self::scope::{Scope, Scopes};
+#[cfg(feature = "stream")]
+#[doc(cfg(feature = "stream"))]
+pub use self::store::SqliteTokenStore;
+pub use self::store::{JsonTokenStore, MemoryTokenStore, Stored, TokenStore};
+#[cfg(feature = "totp")]
+#[doc(cfg(feature = "totp"))]
+pub use self::totp::Totp;
 
 #[derive(Deserialize, Debug, Clone)]
 #[serde(untagged)]
@@ -16,6 +44,10 @@ pub(crate) enum AuthResponse {
     AuthData {
         access_token: String,
         expires_in: u64,
+        #[serde(default)]
+        refresh_token: Option<String>,
+        #[serde(default)]
+        scope: Option<String>,
     },
     ErrorData {
         error: String,
@@ -26,36 +58,298 @@ pub(crate) enum AuthResponse {
 pub enum Error {
     #[error("client is logged out.")]
     LoggedOut,
+    #[error("clones of the client still use its token.")]
+    InUse,
     #[error("token has expired.")]
     NeedsRefresh,
+    #[error("token is missing the `{0}` scope.")]
+    MissingScope(Scope),
     #[error("reqwest error: {0}")]
     Request(#[from] reqwest::Error),
     #[error("token response error: {0}")]
     Token(String),
+    #[error("no authorization code or refresh token to log in with.")]
+    NoRefreshToken,
+    #[error("the authorization response state doesn't match the one we sent.")]
+    StateMismatch,
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "totp")]
+    #[error("the TOTP secret is not valid base32.")]
+    InvalidTotpSecret,
+    #[cfg(feature = "totp")]
+    #[error("TOTP codes have 6 to 10 digits, not {0}.")]
+    InvalidTotpDigits(u32),
+    #[error("token store error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[cfg(feature = "stream")]
+    #[error("token store error: {0}")]
+    Sql(#[from] sqlx::Error),
 }
 
 /// Used in case the [`Authenticator`] calls fail.
-type Result<T> = std::result::Result<T, Error>;
+pub(crate) type Result<T> = std::result::Result<T, Error>;
 
 /// Used to authenticate a [`crate::Client`] instance.
 pub trait Authenticator: Clone + Send + Sync {
     /// Logs in to this [`Authenticator`].
-    fn login(&mut self, client: &reqwest::Client)
-        -> impl Future<Output = Result<()>> + Send + Sync;
+    fn login(&mut self, client: &reqwest::Client) -> impl Future<Output = Result<()>> + Send;
 
     /// Logs out of this [`Authenticator`].
-    fn logout(
-        &mut self,
-        client: &reqwest::Client,
-    ) -> impl Future<Output = Result<()>> + Send + Sync;
+    fn logout(&mut self, client: &reqwest::Client) -> impl Future<Output = Result<()>> + Send;
 
     /// # Errors
     /// Returns [`Err`] if the user isn't logged in.
     /// If the [`Authenticator`] is [`Anon`], then it cannot fail.
     fn auth_request(&self, req: reqwest::RequestBuilder) -> Result<reqwest::RequestBuilder>;
 
+    /// Returns `true` if the token of this [`Authenticator`] expires within `margin`
+    /// and [`Authenticator::login`] should be called before making another request.
+    ///
+    /// A logged out [`Authenticator`] has nothing to refresh, so its requests fail with
+    /// [`Error::LoggedOut`] instead of logging in again.
+    fn needs_refresh(&self, _margin: Duration) -> bool {
+        false
+    }
+
+    /// Returns `true` if this [`Authenticator`] was granted `scope`.
+    ///
+    /// If we don't know which scopes were granted, we assume it was.
+    fn has_scope(&self, scope: Scope) -> bool {
+        self.token().is_none_or(|token| token.has_scope(scope))
+    }
+
+    /// Returns the current [`Token`], if this [`Authenticator`] is logged in.
+    fn token(&self) -> Option<&Token> {
+        None
+    }
+
+    /// Restores a [`Token`] obtained from a previous session, e.g. from a [`TokenStore`].
+    fn restore(&mut self, _token: Token) {}
+
+    /// Points this [`Authenticator`] at `endpoints` instead of Reddit's.
+    fn set_endpoints(&mut self, _endpoints: Endpoints) {}
+
     /// Returns the base [`Url`] of this Reddit [`Authenticator`].
     fn base_url(&self) -> Url {
         Url::parse("https://oauth.reddit.com/").expect("this to be a valid url")
     }
 }
+
+/// An [`Authenticator`] which logs in to Reddit, so a [`crate::Client`] using it can log
+/// out, or refresh its token on demand.
+pub trait Authenticated: Authenticator {}
+
+impl Authenticated for Password {}
+impl Authenticated for Application {}
+impl Authenticated for Code {}
+impl Authenticated for AnyAuth {}
+impl<A: Authenticated, S: TokenStore> Authenticated for Stored<A, S> {}
+
+/// A bearer token along with its expiry bookkeeping.
+///
+/// The expiry is kept as wall-clock time, so a [`Token`] can be saved to a [`TokenStore`]
+/// and reused after a restart.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Token {
+    #[serde(rename = "access_token")]
+    value: String,
+    #[serde(rename = "refresh_token", default)]
+    refresh: Option<String>,
+    /// When this token expires, in seconds since the unix epoch.
+    expires_at: Timestamp,
+    /// The scopes Reddit granted, if it told us.
+    #[serde(default)]
+    scopes: Option<Scopes>,
+}
+
+impl Token {
+    /// Creates a new [`Token`] that expires in `expires_in`.
+    #[must_use]
+    pub fn new(access_token: String, refresh_token: Option<String>, expires_in: Duration) -> Self {
+        Self {
+            value: access_token,
+            refresh: refresh_token,
+            expires_at: Timestamp::now() + expires_in,
+            scopes: None,
+        }
+    }
+
+    /// Sets the scopes granted to this [`Token`].
+    #[must_use]
+    pub fn with_scopes(mut self, scopes: Scopes) -> Self {
+        self.scopes = Some(scopes);
+        self
+    }
+
+    /// Returns the scopes granted to this [`Token`], if Reddit told us.
+    #[must_use]
+    pub const fn scopes(&self) -> Option<&Scopes> {
+        self.scopes.as_ref()
+    }
+
+    /// Returns `true` if this [`Token`] was granted `scope`, or if we don't know which scopes
+    /// it was granted.
+    #[must_use]
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.contains(&Scope::All) || scopes.contains(&scope))
+    }
+
+    /// Returns `true` if this [`Token`] has already expired.
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        self.expires_within(Duration::ZERO)
+    }
+
+    /// Returns `true` if this [`Token`] expires within `margin`.
+    #[must_use]
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        Timestamp::now() + margin >= self.expires_at
+    }
+
+    /// Returns when this [`Token`] expires.
+    #[must_use]
+    pub const fn expires_at(&self) -> Timestamp {
+        self.expires_at
+    }
+
+    /// Returns the access token.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns the refresh token, if Reddit issued one.
+    #[must_use]
+    pub fn refresh_token(&self) -> Option<&str> {
+        self.refresh.as_deref()
+    }
+}
+
+impl TryFrom<AuthResponse> for Token {
+    type Error = Error;
+
+    fn try_from(value: AuthResponse) -> Result<Self> {
+        match value {
+            AuthResponse::AuthData {
+                access_token,
+                expires_in,
+                refresh_token,
+                scope,
+            } => {
+                let token = Self::new(access_token, refresh_token, Duration::from_secs(expires_in));
+
+                Ok(match scope {
+                    Some(scope) => token.with_scopes(Scope::parse_list(&scope)),
+                    None => token,
+                })
+            }
+            AuthResponse::ErrorData { error } => Err(Error::Token(error)),
+        }
+    }
+}
+
+impl std::fmt::Debug for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Token")
+            .field("access_token", &"[redacted]")
+            .field(
+                "refresh_token",
+                if self.refresh.is_none() {
+                    &"none"
+                } else {
+                    &"[redacted]"
+                },
+            )
+            .field("expires_at", &self.expires_at())
+            .field("scopes", &self.scopes)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The [`Endpoints`] and [`Token`] of an [`Authenticator`] which logs in through OAuth.
+#[derive(Clone, Default)]
+pub(crate) struct TokenState {
+    endpoints: Arc<Endpoints>,
+    token: Option<Token>,
+}
+
+impl TokenState {
+    /// Authenticates `req` with the token, like [`Authenticator::auth_request`].
+    fn auth_request(&self, req: reqwest::RequestBuilder) -> Result<reqwest::RequestBuilder> {
+        let Some(ref token) = self.token else {
+            return Err(Error::LoggedOut);
+        };
+
+        if token.is_expired() {
+            Err(Error::NeedsRefresh)
+        } else {
+            Ok(req.bearer_auth(token.as_str()))
+        }
+    }
+
+    /// Returns `true` if the token expires within `margin`, like
+    /// [`Authenticator::needs_refresh`].
+    fn needs_refresh(&self, margin: Duration) -> bool {
+        // if we're logged out, there's nothing to refresh.
+        self.token
+            .as_ref()
+            .is_some_and(|token| token.expires_within(margin))
+    }
+
+    fn set_endpoints(&mut self, endpoints: Endpoints) {
+        self.endpoints = Arc::new(endpoints);
+    }
+
+    /// Returns the base [`Url`] of the requests made with the token.
+    fn base_url(&self) -> Url {
+        self.endpoints.oauth.clone()
+    }
+}
+
+/// Requests a new [`Token`] from the `access_token` endpoint.
+pub(crate) async fn request_token(
+    client: &reqwest::Client,
+    endpoints: &Endpoints,
+    client_id: &str,
+    client_secret: &str,
+    form: &[(&str, &str)],
+) -> Result<Token> {
+    client
+        .post(endpoints.token.clone())
+        .form(form)
+        .basic_auth(client_id, Some(client_secret))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<AuthResponse>()
+        .await?
+        .try_into()
+}
+
+/// Revokes `token` through the `revoke_token` endpoint.
+///
+/// `hint` is either `access_token` or `refresh_token`.
+pub(crate) async fn revoke_token(
+    client: &reqwest::Client,
+    endpoints: &Endpoints,
+    client_id: &str,
+    client_secret: &str,
+    token: &str,
+    hint: &str,
+) -> Result<()> {
+    let form = [("token", token), ("token_type_hint", hint)];
+
+    client
+        .post(endpoints.revoke.clone())
+        .form(&form)
+        .basic_auth(client_id, Some(client_secret))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
@@ -1,10 +1,12 @@
 use std::{sync::Arc, time::Duration};
 
-use tokio::time::Instant;
-
 use url::Url;
 
-use crate::auth::{AuthResponse, Authenticator, Error};
+#[cfg(feature = "totp")]
+use crate::auth::Totp;
+use crate::auth::{
+    request_token, revoke_token, Authenticator, Endpoints, Error, Scope, Scopes, Token, TokenState,
+};
 
 #[derive(Clone)]
 pub struct Auth {
@@ -12,81 +14,87 @@ pub struct Auth {
     client_secret: Arc<str>,
     username: Arc<str>,
     password: Arc<str>,
+    scopes: Option<Scopes>,
+    #[cfg(feature = "totp")]
+    totp: Option<Totp>,
 
-    token: Option<Arc<str>>,
-    expires_in: Option<Duration>,
-    refreshed_at: Option<Instant>,
+    state: TokenState,
 }
 
 impl Authenticator for Auth {
     fn auth_request(&self, req: reqwest::RequestBuilder) -> super::Result<reqwest::RequestBuilder> {
-        let Some(ref token) = self.token else {
-            return Err(Error::LoggedOut);
-        };
-        let expires_in = self.expires_in.unwrap();
-        let refreshed_at = self.refreshed_at.unwrap();
+        self.state.auth_request(req)
+    }
 
-        if refreshed_at.elapsed() >= expires_in {
-            Err(Error::NeedsRefresh)
-        } else {
-            Ok(req.bearer_auth(token))
-        }
+    fn needs_refresh(&self, margin: Duration) -> bool {
+        self.state.needs_refresh(margin)
+    }
+
+    fn token(&self) -> Option<&Token> {
+        self.state.token.as_ref()
+    }
+
+    fn set_endpoints(&mut self, endpoints: Endpoints) {
+        self.state.set_endpoints(endpoints);
+    }
+
+    fn base_url(&self) -> Url {
+        self.state.base_url()
+    }
+
+    fn restore(&mut self, token: Token) {
+        self.state.token = Some(token);
     }
 
     async fn login(&mut self, client: &reqwest::Client) -> super::Result<()> {
-        let url = Url::parse("https://www.reddit.com/api/v1/access_token")
-            .expect("this to be a valid url");
+        // accounts with 2FA send the current code along with the password.
+        #[cfg(feature = "totp")]
+        let password = match self.totp {
+            Some(ref totp) => format!("{}:{}", self.password, totp.fresh_code().await),
+            None => self.password.to_string(),
+        };
+        #[cfg(not(feature = "totp"))]
+        let password = self.password.to_string();
 
-        let form = [
+        let scope = self.scopes.as_ref().map(Scope::join);
+        let mut form = vec![
             ("grant_type", "password"),
             ("username", &self.username),
-            ("password", &self.password),
+            ("password", &password),
         ];
 
-        let token_response = client
-            .post(url)
-            .form(&form)
-            .basic_auth(&self.client_id, Some(&self.client_secret))
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<AuthResponse>()
-            .await?;
-
-        match token_response {
-            AuthResponse::AuthData {
-                access_token,
-                expires_in,
-            } => {
-                self.token = Some(Arc::from(access_token));
-                self.refreshed_at = Some(Instant::now());
-                self.expires_in = Some(Duration::from_secs(expires_in));
-
-                Ok(())
-            }
-            AuthResponse::ErrorData { error } => Err(Error::Token(error)),
+        if let Some(ref scope) = scope {
+            form.push(("scope", scope));
         }
+
+        let token = request_token(
+            client,
+            &self.state.endpoints,
+            &self.client_id,
+            &self.client_secret,
+            &form,
+        )
+        .await?;
+        self.state.token = Some(token);
+
+        Ok(())
     }
 
     async fn logout(&mut self, client: &reqwest::Client) -> super::Result<()> {
-        match self.token {
+        match self.state.token {
             None => Err(Error::LoggedOut),
             Some(ref token) => {
-                let form = [
-                    ("token", token.as_ref()),
-                    ("token_type_hint", "access_token"),
-                ];
-
-                client
-                    .post("https://www.reddit.com/api/v1/revoke_token")
-                    .form(&form)
-                    .basic_auth(&self.client_id, Some(&self.client_secret))
-                    .send()
-                    .await?
-                    .error_for_status()?;
-
-                self.token = None;
-                self.expires_in = None;
+                revoke_token(
+                    client,
+                    &self.state.endpoints,
+                    &self.client_id,
+                    &self.client_secret,
+                    token.as_str(),
+                    "access_token",
+                )
+                .await?;
+
+                self.state.token = None;
                 Ok(())
             }
         }
@@ -105,11 +113,35 @@ impl Auth {
             client_secret: client_secret.into(),
             username: username.into(),
             password: password.into(),
-            token: None,
-            expires_in: None,
-            refreshed_at: None,
+            scopes: None,
+            #[cfg(feature = "totp")]
+            totp: None,
+            state: TokenState::default(),
         }
     }
+
+    /// Sets the [`Totp`] generator for accounts with two-factor authentication enabled.
+    #[cfg(feature = "totp")]
+    #[doc(cfg(feature = "totp"))]
+    #[must_use]
+    pub fn with_totp(mut self, totp: Totp) -> Self {
+        self.totp = Some(totp);
+        self
+    }
+
+    /// Points this [`Auth`] at `endpoints` instead of Reddit's.
+    #[must_use]
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.set_endpoints(endpoints);
+        self
+    }
+
+    /// Sets the scopes to request, instead of every scope the app can access.
+    #[must_use]
+    pub fn scopes<I: IntoIterator<Item = Scope>>(mut self, scopes: I) -> Self {
+        self.scopes = Some(scopes.into_iter().collect());
+        self
+    }
 }
 
 impl std::fmt::Debug for Auth {
@@ -121,7 +153,7 @@ impl std::fmt::Debug for Auth {
             .field("password", &"[redacted]")
             .field(
                 "token",
-                if self.token.is_none() {
+                if self.state.token.is_none() {
                     &"not logged in"
                 } else {
                     &"[redacted]"
@@ -0,0 +1,182 @@
+use std::collections::BTreeSet;
+
+/// An OAuth scope, which grants access to a subset of Reddit's API.
+///
+/// See <https://www.reddit.com/api/v1/scopes> for what each one allows.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Every scope.
+    #[serde(rename = "*")]
+    All,
+    Account,
+    Creddits,
+    Edit,
+    Flair,
+    History,
+    Identity,
+    LiveManage,
+    ModConfig,
+    ModContributors,
+    ModFlair,
+    ModLog,
+    ModMail,
+    ModOthers,
+    ModPosts,
+    ModSelf,
+    ModTraffic,
+    ModWiki,
+    MySubreddits,
+    PrivateMessages,
+    Read,
+    Report,
+    Save,
+    StructuredStyles,
+    Submit,
+    Subscribe,
+    Vote,
+    WikiEdit,
+    WikiRead,
+}
+
+/// A set of [`Scope`]s.
+pub type Scopes = BTreeSet<Scope>;
+
+impl Scope {
+    /// Every [`Scope`], except [`Scope::All`].
+    pub const VARIANTS: [Self; 28] = [
+        Self::Account,
+        Self::Creddits,
+        Self::Edit,
+        Self::Flair,
+        Self::History,
+        Self::Identity,
+        Self::LiveManage,
+        Self::ModConfig,
+        Self::ModContributors,
+        Self::ModFlair,
+        Self::ModLog,
+        Self::ModMail,
+        Self::ModOthers,
+        Self::ModPosts,
+        Self::ModSelf,
+        Self::ModTraffic,
+        Self::ModWiki,
+        Self::MySubreddits,
+        Self::PrivateMessages,
+        Self::Read,
+        Self::Report,
+        Self::Save,
+        Self::StructuredStyles,
+        Self::Submit,
+        Self::Subscribe,
+        Self::Vote,
+        Self::WikiEdit,
+        Self::WikiRead,
+    ];
+
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::All => "*",
+            Self::Account => "account",
+            Self::Creddits => "creddits",
+            Self::Edit => "edit",
+            Self::Flair => "flair",
+            Self::History => "history",
+            Self::Identity => "identity",
+            Self::LiveManage => "livemanage",
+            Self::ModConfig => "modconfig",
+            Self::ModContributors => "modcontributors",
+            Self::ModFlair => "modflair",
+            Self::ModLog => "modlog",
+            Self::ModMail => "modmail",
+            Self::ModOthers => "modothers",
+            Self::ModPosts => "modposts",
+            Self::ModSelf => "modself",
+            Self::ModTraffic => "modtraffic",
+            Self::ModWiki => "modwiki",
+            Self::MySubreddits => "mysubreddits",
+            Self::PrivateMessages => "privatemessages",
+            Self::Read => "read",
+            Self::Report => "report",
+            Self::Save => "save",
+            Self::StructuredStyles => "structuredstyles",
+            Self::Submit => "submit",
+            Self::Subscribe => "subscribe",
+            Self::Vote => "vote",
+            Self::WikiEdit => "wikiedit",
+            Self::WikiRead => "wikiread",
+        }
+    }
+
+    /// Parses a space separated list of scopes, like the one Reddit sends back with a token.
+    ///
+    /// Unknown scopes are ignored.
+    #[must_use]
+    pub fn parse_list(scopes: &str) -> Scopes {
+        scopes
+            .split_whitespace()
+            .filter_map(|s| s.parse().ok())
+            .collect()
+    }
+
+    /// Joins `scopes` into a space separated list, like the one Reddit expects.
+    #[must_use]
+    pub fn join<'a, I: IntoIterator<Item = &'a Self>>(scopes: I) -> String {
+        scopes
+            .into_iter()
+            .map(Self::as_str)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl std::str::FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "*" {
+            return Ok(Self::All);
+        }
+
+        Self::VARIANTS
+            .into_iter()
+            .find(|scope| scope.as_str() == s)
+            .ok_or_else(|| format!("unknown scope {s}"))
+    }
+}
+
+impl std::fmt::Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scope;
+
+    #[test]
+    fn test_parse_list() {
+        let scopes = Scope::parse_list("identity read  modposts unknown");
+
+        assert_eq!(
+            scopes.into_iter().collect::<Vec<_>>(),
+            vec![Scope::Identity, Scope::ModPosts, Scope::Read]
+        );
+        assert!(Scope::parse_list("*").contains(&Scope::All));
+    }
+
+    #[test]
+    fn test_serde_matches_as_str() {
+        for scope in Scope::VARIANTS {
+            assert_eq!(
+                serde_json::to_value(scope).unwrap(),
+                serde_json::Value::from(scope.as_str())
+            );
+        }
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+#[cfg(feature = "stream")]
+use sqlx::{Executor, Row, SqlitePool};
+use url::Url;
+
+use crate::auth::{Authenticator, Endpoints, Result, Scope, Token};
+
+/// Somewhere to save [`Token`]s so they survive a restart.
+pub trait TokenStore: Clone + Send + Sync {
+    /// Loads the [`Token`] saved under `key`, if there's one.
+    fn load(&self, key: &str) -> impl Future<Output = Result<Option<Token>>> + Send;
+
+    /// Saves `token` under `key`, replacing the previous one.
+    fn save(&self, key: &str, token: &Token) -> impl Future<Output = Result<()>> + Send;
+
+    /// Removes the [`Token`] saved under `key`.
+    fn remove(&self, key: &str) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// An [`Authenticator`] that reuses the [`Token`] saved in a [`TokenStore`] instead of logging
+/// in again, and saves every new one.
+#[derive(Clone, Debug)]
+pub struct Stored<A, S> {
+    inner: A,
+    store: S,
+    key: Arc<str>,
+}
+
+impl<A, S> Stored<A, S>
+where
+    A: Authenticator,
+    S: TokenStore,
+{
+    /// Wraps `inner`, saving its [`Token`]s in `store` under `key`.
+    pub fn new<K: Into<Arc<str>>>(inner: A, store: S, key: K) -> Self {
+        Self {
+            inner,
+            store,
+            key: key.into(),
+        }
+    }
+
+    /// Returns the wrapped [`Authenticator`].
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+}
+
+impl<A, S> Authenticator for Stored<A, S>
+where
+    A: Authenticator,
+    S: TokenStore,
+{
+    async fn login(&mut self, client: &reqwest::Client) -> Result<()> {
+        // only look at the store the first time around, refreshes must hit Reddit.
+        if self.inner.token().is_none() {
+            if let Some(token) = self.store.load(&self.key).await? {
+                self.inner.restore(token);
+
+                if !self.inner.needs_refresh(Duration::ZERO) {
+                    return Ok(());
+                }
+            }
+        }
+
+        self.inner.login(client).await?;
+
+        if let Some(token) = self.inner.token() {
+            self.store.save(&self.key, token).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn logout(&mut self, client: &reqwest::Client) -> Result<()> {
+        self.inner.logout(client).await?;
+        self.store.remove(&self.key).await
+    }
+
+    fn auth_request(&self, req: reqwest::RequestBuilder) -> Result<reqwest::RequestBuilder> {
+        self.inner.auth_request(req)
+    }
+
+    fn needs_refresh(&self, margin: Duration) -> bool {
+        self.inner.needs_refresh(margin)
+    }
+
+    fn has_scope(&self, scope: Scope) -> bool {
+        self.inner.has_scope(scope)
+    }
+
+    fn token(&self) -> Option<&Token> {
+        self.inner.token()
+    }
+
+    fn restore(&mut self, token: Token) {
+        self.inner.restore(token);
+    }
+
+    fn set_endpoints(&mut self, endpoints: Endpoints) {
+        self.inner.set_endpoints(endpoints);
+    }
+
+    fn base_url(&self) -> Url {
+        self.inner.base_url()
+    }
+}
+
+/// A [`TokenStore`] that keeps [`Token`]s in memory.
+///
+/// Clones share the same tokens.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryTokenStore(Arc<Mutex<HashMap<String, Token>>>);
+
+impl MemoryTokenStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn tokens(&self) -> std::sync::MutexGuard<'_, HashMap<String, Token>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl TokenStore for MemoryTokenStore {
+    async fn load(&self, key: &str) -> Result<Option<Token>> {
+        Ok(self.tokens().get(key).cloned())
+    }
+
+    async fn save(&self, key: &str, token: &Token) -> Result<()> {
+        self.tokens().insert(key.to_string(), token.clone());
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        self.tokens().remove(key);
+        Ok(())
+    }
+}
+
+/// A [`TokenStore`] that keeps [`Token`]s in a JSON file, as an object keyed by `key`.
+///
+/// Clones take turns updating the file, so share one between clients instead of creating a
+/// new one for the same file. The file is only readable by its owner, on unix.
+#[derive(Clone, Debug)]
+pub struct JsonTokenStore {
+    path: Arc<Path>,
+    /// Held while reading, changing and writing back the file, so no update is lost.
+    lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl JsonTokenStore {
+    #[must_use]
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: Arc::from(path.into()),
+            lock: Arc::default(),
+        }
+    }
+
+    async fn read(&self) -> Result<HashMap<String, Token>> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes to a temporary file first, so we never leave a half-written file behind.
+    async fn write(&self, tokens: &HashMap<String, Token>) -> Result<()> {
+        let path = Arc::clone(&self.path);
+        let bytes = serde_json::to_vec_pretty(tokens)?;
+
+        tokio::task::spawn_blocking(move || {
+            let tmp = path.with_extension("tmp");
+            write_private(&tmp, &bytes)?;
+            std::fs::rename(&tmp, &path)
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+
+        Ok(())
+    }
+}
+
+/// Writes `bytes` to `path`, which only its owner can read, since it holds bearer tokens.
+fn write_private(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(path)?;
+    // the file may be left over from before, with whatever permissions it had.
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+impl TokenStore for JsonTokenStore {
+    async fn load(&self, key: &str) -> Result<Option<Token>> {
+        Ok(self.read().await?.remove(key))
+    }
+
+    async fn save(&self, key: &str, token: &Token) -> Result<()> {
+        let _guard = self.lock.lock().await;
+
+        let mut tokens = self.read().await?;
+        tokens.insert(key.to_string(), token.clone());
+        self.write(&tokens).await
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        let _guard = self.lock.lock().await;
+
+        let mut tokens = self.read().await?;
+        if tokens.remove(key).is_some() {
+            self.write(&tokens).await?;
+        }
+        Ok(())
+    }
+}
+
+/// A [`TokenStore`] that keeps [`Token`]s in a `SQLite` database.
+#[cfg(feature = "stream")]
+#[derive(Clone, Debug)]
+pub struct SqliteTokenStore(SqlitePool);
+
+#[cfg(feature = "stream")]
+impl SqliteTokenStore {
+    /// # Errors
+    /// This function will return an [`crate::auth::Error`] if the table creation fails.
+    pub async fn new(pool: SqlitePool) -> Result<Self> {
+        pool.execute(
+            r"CREATE TABLE IF NOT EXISTS token (
+                key TEXT PRIMARY KEY NOT NULL,
+                token TEXT NOT NULL
+            )",
+        )
+        .await?;
+
+        Ok(Self(pool))
+    }
+}
+
+#[cfg(feature = "stream")]
+impl TokenStore for SqliteTokenStore {
+    async fn load(&self, key: &str) -> Result<Option<Token>> {
+        let row = sqlx::query("SELECT token FROM token WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.0)
+            .await?;
+
+        match row {
+            None => Ok(None),
+            Some(row) => Ok(Some(serde_json::from_str(row.try_get("token")?)?)),
+        }
+    }
+
+    async fn save(&self, key: &str, token: &Token) -> Result<()> {
+        sqlx::query("INSERT OR REPLACE INTO token(key, token) VALUES (?, ?)")
+            .bind(key)
+            .bind(serde_json::to_string(token)?)
+            .execute(&self.0)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        sqlx::query("DELETE FROM token WHERE key = ?")
+            .bind(key)
+            .execute(&self.0)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{JsonTokenStore, MemoryTokenStore, TokenStore};
+    use crate::auth::Token;
+    use crate::Timestamp;
+
+    #[test]
+    fn test_token_expiry() {
+        let token = Token::new("token".to_string(), None, Duration::from_hours(1));
+
+        assert!(!token.is_expired());
+        assert!(!token.expires_within(Duration::from_mins(1)));
+        assert!(token.expires_within(Duration::from_hours(2)));
+
+        let token = Token::new("token".to_string(), None, Duration::ZERO);
+        assert!(token.is_expired());
+    }
+
+    #[test]
+    fn test_token_stored_expiry() {
+        // tokens saved with whole seconds still load.
+        let token: Token =
+            serde_json::from_str(r#"{"access_token": "token", "expires_at": 1686000000}"#).unwrap();
+        assert_eq!(token.expires_at(), Timestamp::from_unix(1_686_000_000));
+        assert!(token.is_expired());
+
+        let json = serde_json::to_string(&token).unwrap();
+        assert_eq!(serde_json::from_str::<Token>(&json).unwrap(), token);
+    }
+
+    #[tokio::test]
+    async fn test_memory_store() {
+        let store = MemoryTokenStore::new();
+        let token = Token::new("token".to_string(), None, Duration::from_hours(1));
+
+        assert_eq!(store.load("bot").await.unwrap(), None);
+        store.save("bot", &token).await.unwrap();
+        assert_eq!(store.clone().load("bot").await.unwrap(), Some(token));
+        store.remove("bot").await.unwrap();
+        assert_eq!(store.load("bot").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_json_store() {
+        let path = std::env::temp_dir().join(format!("reddit-api-{}.json", std::process::id()));
+        let store = JsonTokenStore::new(&path);
+        let token = Token::new(
+            "token".to_string(),
+            Some("refresh".to_string()),
+            Duration::from_hours(1),
+        );
+
+        assert_eq!(store.load("bot").await.unwrap(), None);
+        store.save("bot", &token).await.unwrap();
+
+        let loaded = JsonTokenStore::new(&path).load("bot").await.unwrap();
+        assert_eq!(loaded.as_ref(), Some(&token));
+        assert_eq!(loaded.unwrap().refresh_token(), Some("refresh"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        store.remove("bot").await.unwrap();
+        assert_eq!(store.load("bot").await.unwrap(), None);
+
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_json_store_concurrent() {
+        let path =
+            std::env::temp_dir().join(format!("reddit-api-{}-many.json", std::process::id()));
+        let store = JsonTokenStore::new(&path);
+        let token = Token::new("token".to_string(), None, Duration::from_hours(1));
+
+        let saves: Vec<_> = (0..8)
+            .map(|i| {
+                let (store, token) = (store.clone(), token.clone());
+                tokio::spawn(async move { store.save(&format!("bot{i}"), &token).await })
+            })
+            .collect();
+        for save in saves {
+            save.await.unwrap().unwrap();
+        }
+
+        // every save made it, none overwrote another.
+        for i in 0..8 {
+            assert!(store.load(&format!("bot{i}")).await.unwrap().is_some());
+        }
+
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
@@ -0,0 +1,261 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
+use url::Url;
+
+use crate::auth::{Anon, Authenticator, Endpoints};
+use crate::{Client, Result, RetryPolicy, Settings, DEFAULT_REFRESH_MARGIN};
+
+/// Builds a [`Client`].
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use reddit_api::Client;
+///
+/// # fn main() -> Result<(), reddit_api::Error> {
+/// let client = Client::builder("my-bot (by u/username)")
+///     .timeout(Duration::from_secs(30))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ClientBuilder {
+    user_agent: String,
+    client: Option<reqwest::Client>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    headers: HeaderMap,
+    base_url: Option<Url>,
+    endpoints: Option<Endpoints>,
+    refresh_margin: Duration,
+    throttle: bool,
+    retry: RetryPolicy,
+}
+
+impl ClientBuilder {
+    /// Creates a new [`ClientBuilder`], which sends `user_agent` with every request.
+    #[must_use = "builder does nothing unless built"]
+    pub fn new(user_agent: &str) -> Self {
+        Self {
+            user_agent: user_agent.to_string(),
+            client: None,
+            timeout: None,
+            connect_timeout: None,
+            headers: HeaderMap::new(),
+            base_url: None,
+            endpoints: None,
+            refresh_margin: DEFAULT_REFRESH_MARGIN,
+            throttle: false,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Uses an existing [`reqwest::Client`], to share its connection pool, proxies, root
+    /// certificates and so on.
+    ///
+    /// The user agent, default headers and timeout are added to every API request, but
+    /// [`ClientBuilder::connect_timeout`] is ignored. Token requests are sent as is, so the
+    /// client should already send a user agent.
+    #[must_use = "builder does nothing unless built"]
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Sets the timeout of each request, from when it starts connecting until the response
+    /// body has finished.
+    #[must_use = "builder does nothing unless built"]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for the connect phase of each request.
+    #[must_use = "builder does nothing unless built"]
+    pub const fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Adds a header sent with every request.
+    #[must_use = "builder does nothing unless built"]
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Adds headers sent with every request.
+    #[must_use = "builder does nothing unless built"]
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Sends every request to `base_url` instead of the [`Authenticator`]'s base URL.
+    #[must_use = "builder does nothing unless built"]
+    pub fn base_url(mut self, base_url: Url) -> Self {
+        self.base_url = Some(base_url);
+        self
+    }
+
+    /// Points the [`Client`], and every [`Authenticator`] it logs in with, at `endpoints`.
+    #[must_use = "builder does nothing unless built"]
+    pub fn endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = Some(endpoints);
+        self
+    }
+
+    /// Sets how long before the token expires the [`Client`] refreshes it.
+    #[must_use = "builder does nothing unless built"]
+    pub const fn refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin = margin;
+        self
+    }
+
+    /// Waits for the rate limit window to reset, instead of sending requests Reddit would
+    /// reject, once [`crate::RateLimit::is_exhausted`]. Defaults to `false`.
+    ///
+    /// The window is shared by every clone of the [`Client`], and every stream built from it.
+    #[must_use = "builder does nothing unless built"]
+    pub const fn throttle(mut self, throttle: bool) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Sets how the [`Client`] retries failed requests.
+    ///
+    /// Defaults to [`RetryPolicy::default`].
+    #[must_use = "builder does nothing unless built"]
+    pub const fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Builds an anonymous [`Client`].
+    ///
+    /// # Errors
+    /// Returns `Err` if the user agent isn't a valid header value, a TLS backend cannot be
+    /// initialized, or the resolver cannot load the system configuration.
+    pub fn build(self) -> Result<Client<Anon>> {
+        let user_agent = HeaderValue::from_str(&self.user_agent)?;
+
+        // headers we must add to each request, since we can't add them to the client.
+        let mut headers = HeaderMap::new();
+        let mut timeout = None;
+
+        let inner = if let Some(client) = self.client {
+            headers = self.headers;
+            headers.insert(USER_AGENT, user_agent);
+            timeout = self.timeout;
+            client
+        } else {
+            let mut client = reqwest::Client::builder()
+                .user_agent(user_agent)
+                .default_headers(self.headers);
+
+            if let Some(t) = self.timeout {
+                client = client.timeout(t);
+            }
+            if let Some(t) = self.connect_timeout {
+                client = client.connect_timeout(t);
+            }
+
+            #[cfg(feature = "rustls")]
+            let client = client.use_rustls_tls();
+
+            client.build()?
+        };
+
+        let settings = Settings {
+            headers,
+            timeout,
+            refresh_margin: self.refresh_margin,
+            endpoints: self.endpoints,
+            base_url: self.base_url,
+            throttle: self.throttle,
+            retry: self.retry,
+        };
+
+        Ok(Client::anonymous(inner, Arc::new(settings)))
+    }
+
+    /// Builds a [`Client`] and logs it in with `authenticator`.
+    ///
+    /// # Errors
+    /// Returns `Err` if [`ClientBuilder::build`] or [`Client::login`] fail.
+    #[allow(clippy::future_not_send)]
+    pub async fn login<A: Authenticator>(self, authenticator: A) -> Result<Client<A>> {
+        self.build()?.login(authenticator).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::header::{HeaderValue, ACCEPT_LANGUAGE, USER_AGENT};
+    use url::Url;
+
+    use super::ClientBuilder;
+    use crate::auth::{Anon, AnyAuth};
+
+    #[test]
+    fn test_build() {
+        let base = Url::parse("http://127.0.0.1:8080/").unwrap();
+        let client = ClientBuilder::new("test")
+            .timeout(Duration::from_secs(10))
+            .base_url(base.clone())
+            .build()
+            .unwrap();
+
+        assert_eq!(client.base_url(), &base);
+        // we built the client, so it sends the headers itself.
+        assert!(client.settings.headers.is_empty());
+        assert_eq!(client.settings.timeout, None);
+    }
+
+    #[tokio::test]
+    async fn test_base_url_login() {
+        let proxy = Url::parse("https://proxy.example.com/reddit/").unwrap();
+        let client = ClientBuilder::new("test")
+            .base_url(proxy.clone())
+            .build()
+            .unwrap();
+
+        // the override outlives logging in and out.
+        let client = client.login(AnyAuth::default()).await.unwrap();
+        assert_eq!(client.base_url(), &proxy);
+
+        let client = client.logout().await.unwrap();
+        assert_eq!(client.base_url(), &proxy);
+
+        let client = ClientBuilder::new("test")
+            .base_url(proxy.clone())
+            .login(Anon::new())
+            .await
+            .unwrap();
+        assert_eq!(client.base_url(), &proxy);
+    }
+
+    #[test]
+    fn test_build_with_client() {
+        let client = ClientBuilder::new("test")
+            .client(reqwest::Client::new())
+            .header(ACCEPT_LANGUAGE, HeaderValue::from_static("en"))
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap();
+
+        assert_eq!(client.settings.headers.get(USER_AGENT).unwrap(), "test");
+        assert_eq!(client.settings.headers.get(ACCEPT_LANGUAGE).unwrap(), "en");
+        assert_eq!(client.settings.timeout, Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_invalid_user_agent() {
+        assert!(ClientBuilder::new("bad\nagent").build().is_err());
+    }
+}
@@ -0,0 +1,458 @@
+//! Loads credentials the same way [PRAW](https://praw.readthedocs.io/en/stable/getting_started/configuration.html)
+//! does, so Python and Rust services can share them.
+//!
+//! Each setting is looked up, from highest to lowest precedence, in:
+//! 1. The `praw_<setting>` environment variable, e.g. `praw_client_id`.
+//! 2. The site's section of the `praw.ini` files.
+//! 3. The `[DEFAULT]` section of the `praw.ini` files.
+//!
+//! The `praw.ini` files are read from the user config directory (`%APPDATA%`,
+//! `$XDG_CONFIG_HOME` or `~/.config`) and then the current directory, so the latter
+//! overrides the former.
+//!
+//! ```no_run
+//! use reddit_api::config::Config;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), reddit_api::Error> {
+//! // reads the site named by `praw_site`, or `[DEFAULT]`.
+//! let client = Config::load()?.login().await?;
+//! let latest = client.subreddit("rust").latest().await?;
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use url::Url;
+
+use crate::auth::{AnyAuth, Application, Code, Endpoints, Password, DO_NOT_TRACK};
+use crate::{Client, ClientBuilder};
+
+/// The section every site falls back to.
+pub const DEFAULT_SITE: &str = "DEFAULT";
+
+/// Where an authorization code app redirects to, if `redirect_uri` isn't set.
+const DEFAULT_REDIRECT_URI: &str = "http://localhost:8080";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("couldn't read {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{}:{line}: expected a section or a `key = value` pair.", path.display())]
+    Parse { path: PathBuf, line: usize },
+    #[error("no section named `{0}` in praw.ini.")]
+    MissingSite(String),
+    #[error("no user agent configured, set `user_agent` or `praw_user_agent`.")]
+    MissingUserAgent,
+    #[error("`{key}` is not a valid url: {source}")]
+    InvalidUrl {
+        key: &'static str,
+        source: url::ParseError,
+    },
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// The settings of a single PRAW site.
+#[derive(Clone, Default)]
+pub struct Config {
+    site: String,
+    values: HashMap<String, String>,
+}
+
+impl Config {
+    /// Loads the site named by the `praw_site` environment variable, or [`DEFAULT_SITE`].
+    ///
+    /// # Errors
+    /// Returns `Err` if a `praw.ini` file can't be read or parsed, or the site has no section.
+    pub fn load() -> Result<Self> {
+        let site = std::env::var("praw_site").unwrap_or_else(|_| DEFAULT_SITE.to_string());
+        Self::load_site(&site)
+    }
+
+    /// Loads `site`.
+    ///
+    /// # Errors
+    /// Returns `Err` if a `praw.ini` file can't be read or parsed, or the site has no section.
+    pub fn load_site(site: &str) -> Result<Self> {
+        let mut ini = Ini::default();
+
+        for path in locations() {
+            match std::fs::read_to_string(&path) {
+                Ok(contents) => ini.parse(&path, &contents)?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(source) => return Err(Error::Io { path, source }),
+            }
+        }
+
+        ini.site(site, |key| std::env::var(key).ok())
+    }
+
+    /// Loads `site` from the `praw.ini` file at `path` alone, still letting environment
+    /// variables override it.
+    ///
+    /// # Errors
+    /// Returns `Err` if the file can't be read or parsed, or the site has no section.
+    pub fn from_file<P: AsRef<Path>>(path: P, site: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let mut ini = Ini::default();
+        ini.parse(path, &contents)?;
+        ini.site(site, |key| std::env::var(key).ok())
+    }
+
+    /// Returns the name of this site.
+    #[must_use]
+    pub fn site(&self) -> &str {
+        &self.site
+    }
+
+    /// Returns the value of `key`, if set.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values
+            .get(&key.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    /// Returns the configured user agent.
+    ///
+    /// # Errors
+    /// Returns [`Error::MissingUserAgent`] if there's none.
+    pub fn user_agent(&self) -> Result<&str> {
+        self.get("user_agent").ok_or(Error::MissingUserAgent)
+    }
+
+    /// Returns the [`Endpoints`] set by `oauth_url` and `reddit_url`, falling back to Reddit's.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidUrl`] if either isn't a valid URL.
+    pub fn endpoints(&self) -> Result<Endpoints> {
+        let mut endpoints = Endpoints::new();
+
+        if let Some(oauth) = self.url("oauth_url")? {
+            endpoints = endpoints.oauth(oauth);
+        }
+
+        if let Some(reddit) = self.url("reddit_url")? {
+            let join = |path| {
+                reddit.join(path).map_err(|source| Error::InvalidUrl {
+                    key: "reddit_url",
+                    source,
+                })
+            };
+
+            endpoints = endpoints
+                .authorize(join("api/v1/authorize")?)
+                .token(join("api/v1/access_token")?)
+                .revoke(join("api/v1/revoke_token")?);
+        }
+
+        Ok(endpoints)
+    }
+
+    /// Returns the [`crate::Authenticator`] these credentials are for, the way PRAW picks it:
+    ///
+    /// - [`Code`] if there's a `refresh_token`.
+    /// - [`Password`] if there's a `username` and `password`.
+    /// - [`Application`] if there's a `client_id`, as an installed app if there's no
+    ///   `client_secret`.
+    /// - [`crate::Anon`] otherwise.
+    ///
+    /// # Errors
+    /// Returns [`Error::InvalidUrl`] if `redirect_uri` isn't a valid URL.
+    pub fn authenticator(&self) -> Result<AnyAuth> {
+        let Some(client_id) = self.get("client_id") else {
+            return Ok(AnyAuth::default());
+        };
+        let client_secret = self.get("client_secret");
+
+        if let Some(refresh_token) = self.get("refresh_token") {
+            let redirect_uri = self.get("redirect_uri").unwrap_or(DEFAULT_REDIRECT_URI);
+            let redirect_uri = Url::parse(redirect_uri).map_err(|source| Error::InvalidUrl {
+                key: "redirect_uri",
+                source,
+            })?;
+
+            let auth = Code::new(client_id, client_secret.unwrap_or_default(), redirect_uri)
+                .with_refresh_token(refresh_token);
+
+            return Ok(auth.into());
+        }
+
+        if let (Some(username), Some(password)) = (self.get("username"), self.get("password")) {
+            let auth = Password::new(
+                client_id,
+                client_secret.unwrap_or_default(),
+                username,
+                password,
+            );
+
+            return Ok(auth.into());
+        }
+
+        if let Some(client_secret) = client_secret {
+            return Ok(Application::new(client_id, client_secret).into());
+        }
+
+        Ok(Application::installed(client_id, DO_NOT_TRACK).into())
+    }
+
+    /// Returns a [`ClientBuilder`] with this site's user agent and endpoints.
+    ///
+    /// # Errors
+    /// Returns `Err` if there's no user agent or the URLs are invalid.
+    pub fn client_builder(&self) -> Result<ClientBuilder> {
+        Ok(Client::builder(self.user_agent()?).endpoints(self.endpoints()?))
+    }
+
+    /// Builds a [`Client`] and logs it in with [`Config::authenticator`].
+    ///
+    /// # Errors
+    /// Returns `Err` if the config is incomplete, or logging in fails.
+    pub async fn login(&self) -> crate::Result<Client<AnyAuth>> {
+        let auth = self.authenticator()?;
+        self.client_builder()?.login(auth).await
+    }
+
+    fn url(&self, key: &'static str) -> Result<Option<Url>> {
+        self.get(key)
+            .map(|url| Url::parse(url).map_err(|source| Error::InvalidUrl { key, source }))
+            .transpose()
+    }
+}
+
+impl std::fmt::Debug for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut keys: Vec<_> = self.values.keys().collect();
+        keys.sort();
+
+        f.debug_struct("Config")
+            .field("site", &self.site)
+            .field("keys", &keys)
+            .finish()
+    }
+}
+
+/// The `praw.ini` files, from lowest to highest precedence.
+fn locations() -> Vec<PathBuf> {
+    let env = |key| std::env::var_os(key).map(PathBuf::from);
+
+    let config_dir = env("APPDATA")
+        .or_else(|| env("XDG_CONFIG_HOME"))
+        .or_else(|| env("HOME").map(|home| home.join(".config")));
+
+    config_dir
+        .into_iter()
+        .chain([PathBuf::new()])
+        .map(|dir| dir.join("praw.ini"))
+        .collect()
+}
+
+/// The sections of one or more `praw.ini` files, later files overriding earlier ones.
+#[derive(Debug, Default)]
+struct Ini {
+    sections: HashMap<String, HashMap<String, String>>,
+}
+
+impl Ini {
+    /// Parses `contents`, the way Python's `configparser` does without interpolation.
+    fn parse(&mut self, path: &Path, contents: &str) -> Result<()> {
+        let mut section: Option<String> = None;
+        let mut last_key: Option<String> = None;
+
+        for (n, line) in contents.lines().enumerate() {
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with(['#', ';']) {
+                continue;
+            }
+
+            // indented lines continue the previous value.
+            if line.starts_with(char::is_whitespace) {
+                if let (Some(section), Some(key)) = (&section, &last_key) {
+                    if let Some(value) = self.values(section).get_mut(key) {
+                        value.push('\n');
+                        value.push_str(trimmed);
+                        continue;
+                    }
+                }
+            }
+
+            if let Some(name) = trimmed.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                let name = name.trim().to_string();
+                self.values(&name);
+                section = Some(name);
+                last_key = None;
+                continue;
+            }
+
+            let parsed = section.as_ref().zip(trimmed.split_once(['=', ':']));
+            let Some((section, (key, value))) = parsed else {
+                return Err(Error::Parse {
+                    path: path.to_path_buf(),
+                    line: n + 1,
+                });
+            };
+
+            let key = key.trim().to_ascii_lowercase();
+            self.values(section)
+                .insert(key.clone(), value.trim().to_string());
+            last_key = Some(key);
+        }
+
+        Ok(())
+    }
+
+    fn values(&mut self, section: &str) -> &mut HashMap<String, String> {
+        self.sections.entry(section.to_string()).or_default()
+    }
+
+    /// Resolves the settings of `site`, looking up environment variables with `env`.
+    fn site<F: Fn(&str) -> Option<String>>(mut self, site: &str, env: F) -> Result<Config> {
+        let mut values = self.sections.remove(DEFAULT_SITE).unwrap_or_default();
+
+        if site != DEFAULT_SITE {
+            let section = self
+                .sections
+                .remove(site)
+                .ok_or_else(|| Error::MissingSite(site.to_string()))?;
+            values.extend(section);
+        }
+
+        for key in KEYS {
+            if let Some(value) = env(&format!("praw_{key}")) {
+                values.insert((*key).to_string(), value);
+            }
+        }
+
+        Ok(Config {
+            site: site.to_string(),
+            values,
+        })
+    }
+}
+
+/// The settings environment variables can override.
+const KEYS: &[&str] = &[
+    "client_id",
+    "client_secret",
+    "password",
+    "redirect_uri",
+    "refresh_token",
+    "username",
+    "user_agent",
+    "oauth_url",
+    "reddit_url",
+];
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{Error, Ini, DEFAULT_SITE};
+    use crate::auth::{AnyAuth, Authenticator};
+
+    const INI: &str = "
+[DEFAULT]
+# shared by every site
+user_agent = shared-bot (by u/someone)
+oauth_url = https://oauth.reddit.com
+
+[bot1]
+client_id: abc
+client_secret = def
+username = bot1
+password = hunter2
+
+[readonly]
+client_id = abc
+client_secret = def
+reddit_url = http://127.0.0.1:8080/
+
+[installed]
+client_id = abc
+";
+
+    fn ini() -> Ini {
+        let mut ini = Ini::default();
+        ini.parse(Path::new("praw.ini"), INI).unwrap();
+        ini
+    }
+
+    #[test]
+    fn test_site_falls_back_to_default() {
+        let config = ini().site("bot1", |_| None).unwrap();
+
+        assert_eq!(config.site(), "bot1");
+        assert_eq!(config.get("CLIENT_ID"), Some("abc"));
+        assert_eq!(config.user_agent().unwrap(), "shared-bot (by u/someone)");
+        assert!(matches!(
+            config.authenticator().unwrap(),
+            AnyAuth::Password(_)
+        ));
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let config = ini()
+            .site("bot1", |key| {
+                (key == "praw_user_agent").then(|| "env-bot".to_string())
+            })
+            .unwrap();
+
+        assert_eq!(config.user_agent().unwrap(), "env-bot");
+    }
+
+    #[test]
+    fn test_authenticator_kind() {
+        let auth = |site| ini().site(site, |_| None).unwrap().authenticator().unwrap();
+
+        assert!(matches!(auth("readonly"), AnyAuth::Application(_)));
+        assert!(matches!(auth("installed"), AnyAuth::Application(_)));
+        assert!(matches!(auth(DEFAULT_SITE), AnyAuth::Anon(_)));
+
+        let code = ini()
+            .site("installed", |key| {
+                (key == "praw_refresh_token").then(|| "token".to_string())
+            })
+            .unwrap();
+        assert!(matches!(code.authenticator().unwrap(), AnyAuth::Code(_)));
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let config = ini().site("readonly", |_| None).unwrap();
+        let mut auth = config.authenticator().unwrap();
+        auth.set_endpoints(config.endpoints().unwrap());
+
+        let endpoints = config.endpoints().unwrap();
+        assert_eq!(
+            endpoints.token.as_str(),
+            "http://127.0.0.1:8080/api/v1/access_token"
+        );
+        assert_eq!(auth.base_url().as_str(), "https://oauth.reddit.com/");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            ini().site("missing", |_| None),
+            Err(Error::MissingSite(_))
+        ));
+
+        let mut ini = Ini::default();
+        assert!(matches!(
+            ini.parse(Path::new("praw.ini"), "[DEFAULT]\nnot a pair"),
+            Err(Error::Parse { line: 2, .. })
+        ));
+    }
+}
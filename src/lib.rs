@@ -3,42 +3,86 @@
 #![feature(doc_cfg)]
 
 pub mod auth;
+mod builder;
+pub mod config;
 pub mod errors;
 pub mod multireddit;
+pub mod pagination;
+mod pool;
+mod ratelimit;
 pub(crate) mod response;
+mod retry;
+mod session;
 pub mod subreddit;
+mod timestamp;
 
 use std::path::{Path, PathBuf};
-#[cfg(feature = "shared_auth")]
 use std::sync::Arc;
+use std::time::Duration;
 
-pub use crate::auth::{Anon, Authenticator, Password};
+pub use crate::auth::{
+    Anon, AnyAuth, Application, Authenticated, Authenticator, Code, Endpoints, Password, Scope,
+};
+pub use crate::builder::ClientBuilder;
 pub use crate::errors::Error;
+pub use crate::pagination::{Page, Paginator, Thing};
+pub use crate::pool::ClientPool;
+pub use crate::ratelimit::RateLimit;
+pub use crate::retry::RetryPolicy;
+pub use crate::timestamp::{OutOfRange, Timestamp};
+use errors::{Context, LogoutError, RedditError};
 #[cfg(feature = "stream")]
 pub use futures_util::{Stream, StreamExt};
 use multireddit::{response::MultiResponse, MultiPath, Multireddit};
-use reqwest::StatusCode;
+use ratelimit::Limiter;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
 use response::Generic;
 use serde::de::DeserializeOwned;
+use session::Session;
 pub use subreddit::Subreddit;
-use tracing::trace;
+use tracing::{trace, warn};
 use url::Url;
 
 type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// How long before a token expires the [`Client`] refreshes it by default.
+pub const DEFAULT_REFRESH_MARGIN: Duration = Duration::from_mins(1);
+
 /// The Reddit [`Client`].
 #[derive(Clone)]
 pub struct Client<A: Authenticator> {
     /// The [`Authenticator`] implementor we're going to use for this [`Client`].
-    #[cfg(feature = "shared_auth")]
-    authenticator: Arc<tokio::sync::RwLock<A>>,
-    /// The [`Authenticator`] implementor we're going to use for this [`Client`].
-    #[cfg(not(feature = "shared_auth"))]
-    authenticator: A,
+    authenticator: Session<A>,
     /// The internal client we use to make requests with.
     inner: reqwest::Client,
     /// The base API URL of this Reddit [`Client`]
     base_url: Url,
+    /// The settings this [`Client`] keeps when logging in or out.
+    settings: Arc<Settings>,
+    /// The [`ClientPool`] this [`Client`] sends its requests through, if any.
+    pool: Option<pool::Shared<A>>,
+    /// The rate limit window, shared by every clone of this [`Client`].
+    rate_limit: Arc<Limiter>,
+}
+
+/// The settings a [`Client`] keeps when logging in or out.
+#[derive(Clone, Debug)]
+struct Settings {
+    /// Headers to add to every request, when `inner` was built by someone else.
+    headers: HeaderMap,
+    /// The timeout of every request, when `inner` was built by someone else.
+    timeout: Option<Duration>,
+    /// How long before the token expires we should refresh it.
+    refresh_margin: Duration,
+    /// The [`Endpoints`] to point authenticators at when logging in, if not Reddit's.
+    endpoints: Option<Endpoints>,
+    /// Where to send every request instead of the [`Authenticator`]'s base URL.
+    base_url: Option<Url>,
+    /// Whether to wait for the rate limit window to reset once it's exhausted.
+    throttle: bool,
+    /// How failed requests are retried.
+    retry: RetryPolicy,
 }
 
 impl<A> Client<A>
@@ -51,6 +95,54 @@ where
         Subreddit::new(subreddit, self.clone())
     }
 
+    /// Sets how long before the token expires this [`Client`] refreshes it.
+    ///
+    /// Defaults to [`DEFAULT_REFRESH_MARGIN`].
+    #[must_use]
+    pub fn with_refresh_margin(mut self, margin: Duration) -> Self {
+        Arc::make_mut(&mut self.settings).refresh_margin = margin;
+        self
+    }
+
+    /// Sets how this [`Client`] retries failed requests.
+    ///
+    /// Defaults to [`RetryPolicy::default`].
+    #[must_use]
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        Arc::make_mut(&mut self.settings).retry = retry;
+        self
+    }
+
+    /// Sends every request to `base_url` instead of the [`Authenticator`]'s base URL, even
+    /// after logging in or out.
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        Arc::make_mut(&mut self.settings).base_url = Some(base_url.clone());
+        self.base_url = base_url;
+        self
+    }
+
+    /// Returns the base URL requests are sent to.
+    ///
+    /// If this [`Client`] belongs to a [`ClientPool`], each member sends them to its own,
+    /// see [`ClientPool::client`].
+    #[must_use]
+    pub const fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Returns a snapshot of the rate limit window, as of the last response to this
+    /// [`Client`] or any of its clones.
+    ///
+    /// If this [`Client`] belongs to a [`ClientPool`], it's the windows of every member
+    /// added up, see [`ClientPool::rate_limit`].
+    #[must_use]
+    pub fn rate_limit(&self) -> RateLimit {
+        self.pool
+            .as_ref()
+            .map_or_else(|| self.rate_limit.snapshot(), |pool| pool.rate_limit())
+    }
+
     /// Get a [`Multireddit`].
     /// # Errors
     /// This function may error if the `Reddit` API returns an error.
@@ -61,159 +153,414 @@ where
     pub async fn multi(&self, multipath: MultiPath) -> Result<Multireddit<A>> {
         let path: PathBuf = multipath.into();
 
-        match self.get_json::<MultiResponse>(&path, &[]).await? {
+        match self
+            .get_json::<MultiResponse>(Scope::Read, &path, &[])
+            .await?
+        {
             Generic::LabeledMulti { data } => Ok(data.into_usable(self)),
-            other => unimplemented!("expected LabeledMulti but got {}", other.kind_name()),
+            other => Err(Error::unexpected("multi", other.kind_name(), &path)),
         }
     }
 
-    #[tracing::instrument(name = "GET", skip_all, fields(path = %path.display()))]
+    /// Sets whether the account this [`Client`] is logged in to sees over 18 content, like
+    /// NSFW subreddits and submissions.
+    ///
+    /// This changes the account's preferences, so it sticks across sessions and applies to
+    /// every app using it. If this [`Client`] belongs to a [`ClientPool`], every member's
+    /// account is changed, one after the other.
+    ///
+    /// API Calls to: [`/api/v1/me/prefs`]
+    /// # Errors
+    /// Returns [`crate::auth::Error::MissingScope`] if this [`Client`] isn't logged in to an
+    /// account, or wasn't granted [`Scope::Account`].
+    ///
+    /// If it belongs to a [`ClientPool`] and some members fail, the others are still changed,
+    /// and it returns [`Error::Pool`] saying which ones were.
+    pub async fn set_over_18(&self, enabled: bool) -> Result<()> {
+        let path: PathBuf = ["api", "v1", "me", "prefs"].iter().collect();
+        let prefs = serde_json::json!({ "over_18": enabled });
+
+        self.write_json::<serde::de::IgnoredAny>(
+            Method::PATCH,
+            Scope::Account,
+            &path,
+            &Body::Json(&prefs),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Makes a `GET` request to `path`, which requires `scope`.
+    ///
+    /// If this [`Client`] belongs to a [`ClientPool`], the request is sent by whichever of
+    /// its members has the most budget left.
+    ///
+    /// # Errors
+    /// Returns [`crate::auth::Error::MissingScope`] without sending the request if the token
+    /// wasn't granted `scope`.
     pub(crate) async fn get_json<T: DeserializeOwned>(
         &self,
+        scope: Scope,
         path: &Path,
         params: &[(&str, String)],
     ) -> Result<T> {
-        let url = build_url(self.base_url.clone(), path, params);
+        self.get(scope, path, params, false).await
+    }
+
+    /// Makes a `GET` request to `path`, one of a subreddit's endpoints, like
+    /// [`Client::get_json`].
+    ///
+    /// Reddit redirects requests for subreddits which don't exist to the subreddit search,
+    /// and for over 18 ones to a page asking to opt in, which we turn into errors.
+    pub(crate) async fn get_subreddit_json<T: DeserializeOwned>(
+        &self,
+        scope: Scope,
+        path: &Path,
+        params: &[(&str, String)],
+    ) -> Result<T> {
+        self.get(scope, path, params, true).await
+    }
 
-        trace!(url = %url, "fetching");
+    #[tracing::instrument(name = "GET", skip_all, fields(path = %path.display()))]
+    async fn get<T: DeserializeOwned>(
+        &self,
+        scope: Scope,
+        path: &Path,
+        params: &[(&str, String)],
+        subreddit: bool,
+    ) -> Result<T> {
+        if let Some(ref pool) = self.pool {
+            return pool.get_json(scope, path, params, subreddit).await;
+        }
 
-        let mut req = self.inner.get(url);
+        let resp = self.send(Method::GET, scope, path, params, None).await?;
+        Self::parse_get(resp, subreddit).await
+    }
 
-        #[cfg(feature = "shared_auth")]
-        {
-            let guard = self.authenticator.read().await;
-            req = guard.auth_request(req)?;
+    /// Makes a `method` request to `path` with `body`, which requires `scope`.
+    ///
+    /// These change the account's state, so if this [`Client`] belongs to a [`ClientPool`],
+    /// every one of its members sends it, and it fails with [`Error::Pool`] unless they all
+    /// succeed.
+    ///
+    /// # Errors
+    /// Returns [`crate::auth::Error::MissingScope`] without sending the request if the token
+    /// wasn't granted `scope`.
+    #[tracing::instrument(skip_all, fields(%method, path = %path.display()))]
+    pub(crate) async fn write_json<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        scope: Scope,
+        path: &Path,
+        body: &Body<'_>,
+    ) -> Result<T> {
+        if let Some(ref pool) = self.pool {
+            return pool.write_json(method, scope, path, body).await;
         }
 
-        #[cfg(not(feature = "shared_auth"))]
-        {
-            req = self.authenticator.auth_request(req)?;
+        let resp = self
+            .send(method.clone(), scope, path, &[], Some(body))
+            .await?;
+        Self::parse(method, resp).await
+    }
+
+    /// Authenticates and sends a `method` request to `path`, which requires `scope`.
+    ///
+    /// `GET` requests are retried according to the [`RetryPolicy`], anything else is only
+    /// sent once.
+    pub(crate) async fn send(
+        &self,
+        method: Method,
+        scope: Scope,
+        path: &Path,
+        params: &[(&str, String)],
+        body: Option<&Body<'_>>,
+    ) -> Result<reqwest::Response> {
+        let url = build_url(self.base_url.clone(), path, params);
+        let retry = if method == Method::GET {
+            self.settings.retry.clone()
+        } else {
+            RetryPolicy::never()
+        };
+
+        trace!(%method, url = %url, "fetching");
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+
+            let req = self
+                .request(method.clone(), scope, url.clone(), body)
+                .await?;
+            self.rate_limit.acquire().await;
+            let res = req.send().await;
+
+            let delay = match res {
+                Ok(ref resp) => {
+                    let status = resp.status();
+                    self.rate_limit.record(status, resp.headers());
+
+                    retry
+                        .is_retryable(status)
+                        .then(|| retry.delay(attempt, Some(status), Some(resp.headers())))
+                        .flatten()
+                }
+                Err(ref e) => RetryPolicy::is_retryable_error(e)
+                    .then(|| retry.delay(attempt, None, None))
+                    .flatten(),
+            };
+
+            let Some(delay) = delay else {
+                return Ok(res?);
+            };
+
+            match res {
+                Ok(resp) => warn!(attempt, ?delay, status = %resp.status(), "retrying request"),
+                Err(e) => warn!(attempt, ?delay, error = %e, "retrying request"),
+            }
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Builds an authenticated `method` request to `url`, which requires `scope`.
+    ///
+    /// Logs in again first if the token is about to expire.
+    async fn request(
+        &self,
+        method: Method,
+        scope: Scope,
+        url: Url,
+        body: Option<&Body<'_>>,
+    ) -> Result<reqwest::RequestBuilder> {
+        let mut req = self
+            .inner
+            .request(method, url)
+            .headers(self.settings.headers.clone());
+
+        match body {
+            Some(Body::Form(form)) => req = req.form(form),
+            Some(Body::Json(json)) => req = req.json(json),
+            None => {}
+        }
+
+        if let Some(timeout) = self.settings.timeout {
+            req = req.timeout(timeout);
+        }
+
+        let req = self
+            .authenticator
+            .authorize(&self.inner, self.settings.refresh_margin, scope, req)
+            .await?;
+
+        Ok(req)
+    }
+
+    /// Turns `resp`, the response to a `GET` request, into `T`, like [`Client::parse`].
+    ///
+    /// If `subreddit` is set, the request was for a subreddit, so being redirected to the
+    /// subreddit search or to the over 18 page means it can't be read.
+    pub(crate) async fn parse_get<T: DeserializeOwned>(
+        resp: reqwest::Response,
+        subreddit: bool,
+    ) -> Result<T> {
+        if let Some(source) = errors::redirect_error(resp.url()).filter(|_| subreddit) {
+            let context = Context::new(Method::GET, resp.url(), resp.status());
+            return Err(Error::api(source, context));
         }
 
-        let resp = req.send().await?;
+        Self::parse(Method::GET, resp).await
+    }
+
+    /// Turns `resp`, the response to a `method` request, into `T`, or into an [`Error`] if
+    /// Reddit sent one.
+    pub(crate) async fn parse<T: DeserializeOwned>(
+        method: Method,
+        resp: reqwest::Response,
+    ) -> Result<T> {
         let status = resp.status();
+        let context = Context::new(method, resp.url(), status);
+
+        let body = resp.bytes().await?;
 
         if status == StatusCode::TOO_MANY_REQUESTS {
-            return Err(crate::Error::Reddit(
-                crate::errors::RedditError::RateLimited,
-            ));
+            return Err(Error::Api {
+                source: RedditError::RateLimited,
+                context: Box::new(context.with_reason(&body)),
+            });
         }
 
         if status.is_client_error() || status.is_server_error() {
-            Err(crate::errors::Error::Reddit(resp.json().await?))
-        } else {
-            Ok(resp.json().await?)
+            let context = context.with_reason(&body);
+
+            let (source, context) = if let Ok(source) = serde_json::from_slice(&body) {
+                (source, context)
+            } else {
+                // not one of Reddit's error bodies, an HTML error page maybe.
+                let message = status.to_string();
+                (RedditError::Simple { message }, context.with_body(&body))
+            };
+
+            return Err(Error::Api {
+                source: source.classify(status, context.reason.as_deref()),
+                context: Box::new(context),
+            });
+        }
+
+        serde_json::from_slice(&body).map_err(|source| Error::Decode {
+            source,
+            context: Box::new(context.with_body(&body)),
+        })
+    }
+}
+
+impl<A: Authenticated> Client<A> {
+    /// Logs out of this [`Client`], returning an anonymous one which reuses its connections.
+    ///
+    /// # Errors
+    /// Returns [`crate::auth::Error::InUse`] without logging out while there are clones of
+    /// this [`Client`], e.g. in a [`Subreddit`] or a [`ClientPool`], which would keep using
+    /// its token. Otherwise returns `Err` if the underlying [`reqwest::Client::post`] call
+    /// fails. Either way, the [`LogoutError`] gives this [`Client`] back.
+    #[tracing::instrument(name = "Logging out", skip_all)]
+    pub async fn logout(mut self) -> Result<Client<Anon>, LogoutError<A>> {
+        match self.authenticator.logout(&self.inner).await {
+            // there's no token to revoke, that's what we wanted anyway.
+            Ok(()) | Err(crate::auth::Error::LoggedOut) => {}
+            Err(e) => {
+                return Err(LogoutError {
+                    client: self,
+                    source: e.into(),
+                })
+            }
         }
+
+        Ok(Client::anonymous(self.inner, self.settings))
+    }
+
+    /// Refreshes this [`Client`]'s token.
+    ///
+    /// The token is refreshed automatically before it expires, so you don't need to call
+    /// this unless you want a new one right away.
+    ///
+    /// # Errors
+    /// Returns `Err` if the token refresh fails.
+    /// See:
+    ///    - [`reqwest::Client::post`]
+    ///    - [`reqwest::Response::json`]
+    #[tracing::instrument(name = "Refreshing token", skip_all)]
+    pub async fn refresh_token(&mut self) -> Result<()> {
+        self.authenticator.login(&self.inner).await?;
+        Ok(())
     }
 }
 
 impl Client<Anon> {
     /// Creates a new, anonymous, [`Client`] instance.
+    ///
+    /// Use [`Client::builder`] to configure it, or to handle errors instead of panicking.
     /// # Panics
     ///
-    /// This method panics if a TLS backend cannot be initialized, or the resolver
-    /// cannot load the system configuration.
+    /// This method panics if the user agent isn't a valid header value, a TLS backend cannot
+    /// be initialized, or the resolver cannot load the system configuration.
     #[must_use]
     pub fn new(user_agent: &str) -> Self {
-        let client = reqwest::Client::builder().user_agent(user_agent);
-
-        #[cfg(feature = "rustls")]
-        let client = client.use_rustls_tls();
-
-        let client = client.build().expect("this to be a valid client");
+        Self::builder(user_agent)
+            .build()
+            .expect("this to be a valid client")
+    }
 
-        let auth = Anon::new();
+    /// Creates an anonymous [`Client`] out of the parts of another one.
+    fn anonymous(inner: reqwest::Client, settings: Arc<Settings>) -> Self {
+        let mut auth = Anon::new();
+        if let Some(ref endpoints) = settings.endpoints {
+            auth.set_endpoints(endpoints.clone());
+        }
 
         Self {
-            base_url: auth.base_url(),
+            base_url: settings.base_url(&auth),
+            authenticator: Session::new(auth),
+            inner,
+            rate_limit: Arc::new(Limiter::new(settings.throttle)),
+            settings,
+            pool: None,
+        }
+    }
 
-            #[cfg(not(feature = "shared_auth"))]
-            authenticator: auth,
-            #[cfg(feature = "shared_auth")]
-            authenticator: Arc::new(tokio::sync::RwLock::new(auth)),
+    /// Creates a [`ClientBuilder`], to configure a [`Client`].
+    #[must_use = "builder does nothing unless built"]
+    pub fn builder(user_agent: &str) -> ClientBuilder {
+        ClientBuilder::new(user_agent)
+    }
 
-            inner: client,
-        }
+    /// Points this [`Client`], and every [`Authenticator`] it logs in with, at `endpoints`
+    /// instead of Reddit's.
+    #[must_use]
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        let auth = Anon::new().with_endpoints(endpoints.clone());
+
+        self.base_url = self.settings.base_url(&auth);
+        self.authenticator = Session::new(auth);
+        Arc::make_mut(&mut self.settings).endpoints = Some(endpoints);
+        self
     }
 
     /// Logs in this [`Anon`] [`Client`] instance.
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```no_run
     /// use reddit_api::{auth, Client};
     ///
     /// # #[tokio::main]
-    /// # async fn main() -> reddit_api::Result<()> {
-    ///  let auth = auth::Password::new(env!("REDDIT_CLIENT_ID"), env!("REDDIT_CLIENT_SECRET"), env!("REDDIT_USERNAME"), env!("REDDIT_PASSWORD"));
-    ///  let mut client = Client::new("test by u/username").login(auth).await.unwrap();
+    /// # async fn main() -> Result<(), reddit_api::Error> {
+    ///  let auth = auth::Password::new("client_id", "client_secret", "username", "password");
+    ///  let client = Client::new("test by u/username").login(auth).await?;
     ///  
     ///  let sub = client.subreddit("argentina").latest().await.unwrap();
     ///  
     ///  println!("{sub:#?}");
     ///  
-    ///  client.logout().await
+    ///  // we get an anonymous client back.
+    ///  let client = client.logout().await?;
+    ///  # Ok(())
     /// # }
     /// ```
     /// # Errors
     /// Returns `Err` if the underlying [`reqwest::Client::post`] call fails.
     #[tracing::instrument(name = "Logging in", skip_all)]
-    #[allow(unused_mut, clippy::future_not_send)]
+    #[allow(clippy::future_not_send)]
     pub async fn login<A: Authenticator>(self, mut authenticator: A) -> Result<Client<A>> {
+        if let Some(ref endpoints) = self.settings.endpoints {
+            authenticator.set_endpoints(endpoints.clone());
+        }
+
         authenticator.login(&self.inner).await?;
 
         Ok(Client {
             inner: self.inner,
-            base_url: authenticator.base_url(),
-            #[cfg(feature = "shared_auth")]
-            authenticator: Arc::new(tokio::sync::RwLock::new(authenticator)),
-            #[cfg(not(feature = "shared_auth"))]
-            authenticator,
+            base_url: self.settings.base_url(&authenticator),
+            pool: None,
+            // a new account has a budget of its own.
+            rate_limit: Arc::new(Limiter::new(self.settings.throttle)),
+            settings: self.settings,
+            authenticator: Session::new(authenticator),
         })
     }
 }
 
-impl Client<Password> {
-    /// Logs out of this [`Client`].
-    ///
-    /// # Errors
-    /// Returns `Err` if the underlying [`reqwest::Client::post`] call fails.
-    #[tracing::instrument(name = "Logging out", skip_all)]
-    pub async fn logout(&mut self) -> Result<()> {
-        #[cfg(feature = "shared_auth")]
-        {
-            self.authenticator.write().await.logout(&self.inner).await?;
-        }
-        #[cfg(not(feature = "shared_auth"))]
-        {
-            self.authenticator.logout(&self.inner).await?;
-        }
-        // TODO: Make this return an instance of Client<Anon>
-        Ok(())
+impl Settings {
+    /// Returns where requests authenticated by `auth` are sent.
+    fn base_url<A: Authenticator>(&self, auth: &A) -> Url {
+        self.base_url.clone().unwrap_or_else(|| auth.base_url())
     }
+}
 
-    /// Refreshes this [`Client`]'s token.
-    ///
-    /// This is the method you should call if you receive
-    /// [`Error::AuthError`] [`crate::auth::Error::NeedsRefresh`].
-    ///
-    /// # Errors
-    /// Returns `Err` if the token refresh fails.
-    /// See:
-    ///    - [`reqwest::Client::post`]
-    ///    - [`reqwest::Response::json`]
-    #[tracing::instrument(name = "Refreshing token", skip_all)]
-    pub async fn refresh_token(&mut self) -> Result<()> {
-        #[cfg(feature = "shared_auth")]
-        {
-            self.authenticator.write().await.login(&self.inner).await?;
-        }
-        #[cfg(not(feature = "shared_auth"))]
-        {
-            self.authenticator.login(&self.inner).await?;
-        }
-        Ok(())
-    }
+/// The body of a request which isn't a `GET`.
+#[derive(Debug)]
+pub(crate) enum Body<'a> {
+    Form(&'a [(&'a str, &'a str)]),
+    Json(&'a serde_json::Value),
 }
 
 pub(crate) fn build_url(mut base: Url, path: &Path, params: &[(&str, String)]) -> Url {
@@ -251,9 +598,9 @@ where
 mod test {
     use std::path::PathBuf;
 
-    use crate::auth::{Anon, Authenticator, Password};
+    use crate::auth::{self, Anon, AnyAuth, Application, Authenticator, Endpoints, Password};
     use crate::subreddit::feed;
-    use crate::{build_url, Client};
+    use crate::{build_url, Client, Error};
     use dotenv::{dotenv, var};
 
     #[tokio::test]
@@ -285,7 +632,7 @@ mod test {
 
         let client = Client::new(&user_agent).login(auth).await;
         assert!(client.is_ok(), "failed authenticating a client");
-        let mut client = client.unwrap();
+        let client = client.unwrap();
 
         let sub = client.subreddit("argentina");
         let about = sub.about().await;
@@ -301,9 +648,64 @@ mod test {
             .await;
 
         assert!(latest.is_ok());
-        let res = dbg!(client.logout().await);
+        let client = dbg!(client.logout().await);
+
+        assert!(client.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_application_auth() {
+        dotenv().unwrap();
+
+        let username = var("REDDIT_USERNAME").unwrap();
+        let pkg_name = var("CARGO_PKG_NAME").unwrap();
+        let user_agent = format!("{pkg_name} (by u/{username})");
+
+        let client_id = var("REDDIT_CLIENT_ID").unwrap();
+        let client_secret = var("REDDIT_CLIENT_SECRET").unwrap();
+
+        let auth = Application::new(client_id, client_secret);
+
+        let client = Client::new(&user_agent).login(auth).await;
+        assert!(client.is_ok(), "failed authenticating a client");
+        let client = client.unwrap();
+
+        let latest = client.subreddit("argentina").latest().await;
+        assert!(latest.is_ok());
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let local = url::Url::parse("http://127.0.0.1:8080/").unwrap();
+        let endpoints = Endpoints::new().api(local.clone()).oauth(local.clone());
+
+        let client = Client::new("test").with_endpoints(endpoints.clone());
+        assert_eq!(client.base_url(), &local);
+
+        let auth = Password::new("id", "secret", "user", "password").with_endpoints(endpoints);
+        assert_eq!(auth.base_url(), local);
+
+        let proxy = url::Url::parse("https://proxy.example.com/reddit/").unwrap();
+        let client = client.with_base_url(proxy.clone());
+        assert_eq!(client.base_url(), &proxy);
+    }
+
+    #[tokio::test]
+    async fn test_logout() {
+        let local = url::Url::parse("http://127.0.0.1:8080/").unwrap();
+        let endpoints = Endpoints::new().api(local.clone());
+
+        let client = Client::new("test").with_endpoints(endpoints);
+        let client = client.login(AnyAuth::default()).await.unwrap();
+
+        // the subreddit would keep using the token.
+        let sub = client.subreddit("rust");
+        let err = client.logout().await.unwrap_err();
+        assert!(matches!(err.source, Error::AuthError(auth::Error::InUse)));
 
-        assert!(res.is_ok());
+        drop(sub);
+        let client = err.client.logout().await.unwrap();
+        assert_eq!(client.base_url(), &local);
     }
 
     #[test]
@@ -10,12 +10,13 @@ use std::sync::Arc;
 
 use crate::auth::Authenticator;
 use crate::subreddit::Subreddit;
+use crate::Timestamp;
 
 #[derive(Clone, Debug)]
 pub struct Multireddit<A: Authenticator> {
     pub can_edit: bool,
-    pub created: f64,
-    pub created_utc: f64,
+    pub created: Timestamp,
+    pub created_utc: Timestamp,
     pub description_html: Arc<str>,
     pub description_md: Arc<str>,
     pub display_name: Arc<str>,
@@ -76,13 +77,12 @@ impl From<MultiPath> for PathBuf {
 
 #[cfg(test)]
 mod test {
-    use std::{path::PathBuf, time::Duration};
+    use std::path::PathBuf;
 
     use dotenv::{dotenv, var};
-    use futures_util::StreamExt;
 
     use super::MultiPath;
-    use crate::{subreddit::multistream::SetStorage, Client};
+    use crate::Client;
 
     #[test]
     fn multipath_to_pathbuf() {
@@ -110,6 +110,12 @@ mod test {
     #[cfg(feature = "stream")]
     #[tokio::test]
     async fn anon_multi_stream() {
+        use std::time::Duration;
+
+        use futures_util::StreamExt;
+
+        use crate::subreddit::multistream::SetStorage;
+
         dotenv().unwrap();
         let pkg_name = env!("CARGO_PKG_NAME");
         let username = var("REDDIT_USERNAME").unwrap();
@@ -126,14 +132,15 @@ mod test {
         let n = multi
             .stream(SetStorage::new())
             .sort(crate::subreddit::feed::Sort::New)
-            .poll_period(Duration::from_secs(120))
+            .poll_period(Duration::from_mins(2))
             .build(30..=120)
             .unwrap()
             .take(200)
             .take_while(|r| futures_util::future::ready(r.is_ok()))
-            .fold(0, |state, next| async move {
-                next.map(|_| 1).unwrap_or(0) + state
-            })
+            .fold(
+                0,
+                |state, next| async move { next.map_or(0, |_| 1) + state },
+            )
             .await;
 
         assert_eq!(n, 200);
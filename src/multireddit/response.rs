@@ -2,7 +2,7 @@ use crate::{
     auth::Authenticator,
     response::{Generic, RedditUrl},
     subreddit::Subreddit,
-    Client,
+    Client, Timestamp,
 };
 
 use url::Url;
@@ -17,8 +17,8 @@ pub struct SubredditMeta {
 #[derive(Debug, serde::Deserialize)]
 pub struct MultiInternal {
     pub can_edit: bool,
-    pub created: f64,
-    pub created_utc: f64,
+    pub created: Timestamp,
+    pub created_utc: Timestamp,
     pub description_html: String,
     pub description_md: String,
     pub display_name: String,
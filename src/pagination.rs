@@ -0,0 +1,598 @@
+use std::collections::HashSet;
+#[cfg(feature = "stream")]
+use std::collections::VecDeque;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+#[cfg(feature = "stream")]
+use std::task::{Context, Poll};
+
+use serde::de::{DeserializeOwned, IgnoredAny};
+
+use crate::auth::{Authenticator, Scope};
+use crate::response::Generic;
+use crate::subreddit::feed::Options;
+use crate::{Client, Error, Result};
+
+/// Reddit sends at most this many items per request.
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+/// How deep into a listing Reddit lets us go, give or take a few removed items.
+pub const LISTING_HORIZON: u64 = 1000;
+
+/// A page of a listing, along with the cursors to the pages around it.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// The fullname of the last item, which the next page starts after.
+    pub after: Option<String>,
+    /// The fullname of the first item, which the previous page ends before.
+    pub before: Option<String>,
+    /// Whether the listing ended because we reached [`LISTING_HORIZON`], rather than
+    /// because there are no older items.
+    ///
+    /// Reddit doesn't say, so this is a guess based on how deep into the listing we are.
+    pub horizon: bool,
+    /// How many top level comments Reddit left out of this page, along with their replies,
+    /// going by the placeholders it sent instead.
+    ///
+    /// Use [`crate::Subreddit::comment_tree`] to keep the placeholders, or
+    /// [`crate::Subreddit::comments_complete`] to fetch what they stand for.
+    pub omitted: u64,
+}
+
+/// Something Reddit lists, which is identified by its fullname.
+pub trait Thing {
+    /// Returns the base36 identifier of this [`Thing`], without its kind.
+    fn id(&self) -> &str;
+}
+
+impl<T> Page<T> {
+    /// Returns `true` if there are no pages after this one.
+    #[must_use]
+    pub const fn is_last(&self) -> bool {
+        self.after.is_none()
+    }
+
+    /// Turns `listing` into a [`Page`], if it's a listing of `kind`s.
+    pub(crate) fn from_listing(
+        listing: Generic<T>,
+        kind: &'static str,
+        path: &Path,
+    ) -> Result<Self> {
+        let Generic::Listing { data } = listing else {
+            return Err(Error::unexpected("listing", listing.kind_name(), path));
+        };
+
+        let mut omitted = 0;
+        let items = data
+            .children
+            .into_iter()
+            .filter_map(|child| match child {
+                Generic::Link { data } if kind == "link" => Some(Ok(data)),
+                Generic::Comment { data } if kind == "comment" => Some(Ok(data)),
+                // placeholders for comments Reddit left out, see `Subreddit::comment_tree`.
+                Generic::More { data } if kind == "comment" => {
+                    omitted += data.count;
+                    None
+                }
+                other => Some(Err(Error::unexpected(kind, other.kind_name(), path))),
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            items,
+            after: data.after,
+            before: data.before,
+            horizon: false,
+            omitted,
+        })
+    }
+
+    /// Adds the items of `next`, the page which follows this one in the direction we're
+    /// paging, skipping the ones in `ids`.
+    fn join(&mut self, next: Self, backwards: bool, ids: &mut HashSet<String>)
+    where
+        T: Thing,
+    {
+        let fresh = next
+            .items
+            .into_iter()
+            .filter(|item| ids.insert(item.id().to_string()));
+
+        if backwards {
+            // it comes before this one in the listing.
+            self.items.splice(0..0, fresh);
+            self.before = next.before;
+        } else {
+            self.items.extend(fresh);
+            self.after = next.after;
+        }
+        self.omitted += next.omitted;
+    }
+}
+
+/// Returns `true` if a listing which ended after `count` items likely hit the
+/// [`LISTING_HORIZON`].
+const fn at_horizon(count: u64) -> bool {
+    // removed items still count towards it, so we usually get a bit less.
+    count + MAX_PAGE_SIZE > LISTING_HORIZON
+}
+
+impl<T> IntoIterator for Page<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+/// What a listing endpoint sends back.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Shape {
+    /// A single listing of `kind`s, which pages with [`Options`].
+    Listing { kind: &'static str },
+    /// A post and its comments, which all come in one go.
+    Thread,
+}
+
+type PageFuture<T> = Pin<Box<dyn Future<Output = Result<Page<T>>> + Send>>;
+
+/// Fetches a single page of the listing at `path`.
+pub(crate) async fn fetch_page<A, T>(
+    client: &Client<A>,
+    path: &Path,
+    params: &[(&str, String)],
+    shape: Shape,
+) -> Result<Page<T>>
+where
+    A: Authenticator,
+    T: DeserializeOwned,
+{
+    match shape {
+        // listings are subreddit feeds.
+        Shape::Listing { kind } => {
+            let listing = client
+                .get_subreddit_json::<Generic<T>>(Scope::Read, path, params)
+                .await?;
+            Page::from_listing(listing, kind, path)
+        }
+        Shape::Thread => {
+            // the post itself + the comments
+            let (_, comments) = client
+                .get_json::<(IgnoredAny, Generic<T>)>(Scope::Read, path, params)
+                .await?;
+
+            Page::from_listing(comments, "comment", path)
+        }
+    }
+}
+
+/// Fetches [`Options::limit`] items of the listing of `kind`s at `path`, sending as many
+/// requests as needed and skipping items we've already seen, up to the [`LISTING_HORIZON`].
+///
+/// With [`Options::before`] set, it keeps following the `before` cursor instead, and the
+/// items still come in listing order.
+pub(crate) async fn fetch_listing<A, T>(
+    client: &Client<A>,
+    path: &Path,
+    params: &[(&str, String)],
+    mut options: Options,
+    kind: &'static str,
+) -> Result<Page<T>>
+where
+    A: Authenticator,
+    T: DeserializeOwned + Thing,
+{
+    let wanted = options.page_size();
+    let limit = wanted.min(LISTING_HORIZON);
+    let backwards = options.backwards();
+    let mut ids = HashSet::new();
+    let mut page = Page {
+        items: Vec::new(),
+        after: None,
+        before: None,
+        horizon: false,
+        omitted: 0,
+    };
+
+    // Reddit would send its default of 25 items instead.
+    if limit == 0 {
+        return Ok(page);
+    }
+
+    loop {
+        let left = limit - page.items.len() as u64;
+        let mut query = params.to_vec();
+        query.extend(Vec::<(&str, String)>::from(
+            options.clone().limit(left.min(MAX_PAGE_SIZE)),
+        ));
+
+        let next: Page<T> = fetch_page(client, path, &query, Shape::Listing { kind }).await?;
+        let got = next.items.len() as u64;
+
+        if ids.is_empty() {
+            page.after.clone_from(&next.after);
+            page.before.clone_from(&next.before);
+        }
+        options.advance(got, next.after.as_deref(), next.before.as_deref());
+        page.join(next, backwards, &mut ids);
+
+        let cursor = if backwards { &page.before } else { &page.after };
+        if cursor.is_none() || got == 0 {
+            // going backwards, we ran into the newest items instead.
+            page.horizon = !backwards && at_horizon(options.offset());
+            break;
+        }
+        if page.items.len() as u64 >= limit {
+            page.horizon = !backwards && wanted > LISTING_HORIZON;
+            break;
+        }
+    }
+
+    Ok(page)
+}
+
+/// Walks a listing page by page, following the `after` cursor until Reddit runs out of
+/// items or we've seen [`Paginator::max_items`] of them.
+///
+/// With [`Options::before`] set, it follows the `before` cursor instead, towards newer
+/// items. A [`Options::limit`] of 0 yields no pages.
+///
+/// With the `stream` feature enabled, it's also a [`futures_util::Stream`] of items, which
+/// fetches the next page once the last one runs out.
+///
+/// # Example
+/// ```no_run
+/// use reddit_api::subreddit::feed::{Options, Sort};
+/// use reddit_api::Client;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), reddit_api::Error> {
+/// let client = Client::new("my-bot (by u/username)");
+/// let mut pages = client
+///     .subreddit("rust")
+///     .paginate(Sort::New, Options::new())
+///     .max_items(250);
+///
+/// while let Some(page) = pages.next_page().await {
+///     for post in page? {
+///         println!("{}", post.title);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[must_use = "paginators do nothing unless polled"]
+pub struct Paginator<A: Authenticator, T> {
+    client: Client<A>,
+    path: PathBuf,
+    /// The parameters every page is requested with, besides the [`Options`].
+    params: Vec<(&'static str, String)>,
+    options: Options,
+    shape: Shape,
+    max_items: Option<u64>,
+    /// How many items we've handed out so far.
+    seen: u64,
+    /// The ids of the items we've handed out, so we don't hand them out twice when the
+    /// listing shifts between pages.
+    ids: HashSet<String>,
+    done: bool,
+    /// Items of the last page the [`futures_util::Stream`] hasn't handed out yet.
+    #[cfg(feature = "stream")]
+    buffer: VecDeque<T>,
+    pending: Option<PageFuture<T>>,
+}
+
+impl<A, T> Paginator<A, T>
+where
+    A: Authenticator + 'static,
+    T: DeserializeOwned + Thing + Send + 'static,
+{
+    pub(crate) fn new(
+        client: Client<A>,
+        path: PathBuf,
+        params: Vec<(&'static str, String)>,
+        options: Options,
+        shape: Shape,
+    ) -> Self {
+        Self {
+            client,
+            path,
+            params,
+            options,
+            shape,
+            max_items: None,
+            seen: 0,
+            ids: HashSet::new(),
+            done: false,
+            #[cfg(feature = "stream")]
+            buffer: VecDeque::new(),
+            pending: None,
+        }
+    }
+
+    /// Stops after `max_items` items, even if Reddit has more.
+    pub const fn max_items(mut self, max_items: u64) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Fetches the next [`Page`], or returns `None` once there are no more.
+    ///
+    /// Items still buffered by the [`futures_util::Stream`] implementation aren't part of it.
+    ///
+    /// # Errors
+    /// Returns `Err` if the underlying request fails. Calling it again retries the same page.
+    pub async fn next_page(&mut self) -> Option<Result<Page<T>>> {
+        let fut = match self.pending.take() {
+            Some(fut) => fut,
+            None => self.fetch()?,
+        };
+
+        Some(fut.await.map(|page| self.advance(page)))
+    }
+
+    /// Starts fetching the next page, or returns `None` if there are no more.
+    fn fetch(&self) -> Option<PageFuture<T>> {
+        let left = self.max_items.map(|max| max.saturating_sub(self.seen));
+        if self.done || left == Some(0) || self.options.page_size() == 0 {
+            return None;
+        }
+
+        let client = self.client.clone();
+        let path = self.path.clone();
+        let mut params = self.params.clone();
+        let shape = self.shape;
+
+        if let Shape::Listing { .. } = shape {
+            let page_size = self.options.page_size().min(MAX_PAGE_SIZE);
+            let limit = left.map_or(page_size, |left| left.min(page_size));
+            let options: Vec<(&str, String)> = self.options.clone().limit(limit).into();
+            params.extend(options);
+        }
+
+        Some(Box::pin(async move {
+            fetch_page(&client, &path, &params, shape).await
+        }))
+    }
+
+    /// Moves past `page`, dropping the items we've already handed out and trimming it down
+    /// to [`Paginator::max_items`].
+    fn advance(&mut self, mut page: Page<T>) -> Page<T> {
+        let got = page.items.len() as u64;
+        let backwards = self.options.backwards();
+        self.options
+            .advance(got, page.after.as_deref(), page.before.as_deref());
+
+        page.items
+            .retain(|item| self.ids.insert(item.id().to_string()));
+        if let Some(max) = self.max_items {
+            let left = usize::try_from(max.saturating_sub(self.seen)).unwrap_or(usize::MAX);
+            page.items.truncate(left);
+        }
+        self.seen += page.items.len() as u64;
+
+        // threads come in one go, and an empty page means Reddit is out of items.
+        let cursor = if backwards { &page.before } else { &page.after };
+        let exhausted = cursor.is_none() || got == 0;
+        if let Shape::Listing { .. } = self.shape {
+            page.horizon = exhausted && !backwards && at_horizon(self.options.offset());
+        }
+        self.done = matches!(self.shape, Shape::Thread) || exhausted;
+
+        page
+    }
+}
+
+#[cfg(feature = "stream")]
+#[doc(cfg(feature = "stream"))]
+impl<A, T> futures_util::Stream for Paginator<A, T>
+where
+    A: Authenticator + 'static,
+    T: DeserializeOwned + Thing + Send + Unpin + 'static,
+{
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(item) = this.buffer.pop_front() {
+                return Poll::Ready(Some(Ok(item)));
+            }
+
+            if this.pending.is_none() {
+                this.pending = this.fetch();
+            }
+            let Some(ref mut fut) = this.pending else {
+                return Poll::Ready(None);
+            };
+
+            let res = std::task::ready!(fut.as_mut().poll(cx));
+            this.pending = None;
+
+            match res {
+                Ok(page) => {
+                    let page = this.advance(page);
+                    this.buffer.extend(page);
+                }
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            }
+        }
+    }
+}
+
+impl<A: Authenticator, T> std::fmt::Debug for Paginator<A, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Paginator")
+            .field("path", &self.path)
+            .field("options", &self.options)
+            .field("seen", &self.seen)
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{fetch_listing, Page, Paginator, Shape, Thing};
+    use crate::response::Generic;
+    use crate::subreddit::feed::Options;
+    use crate::Client;
+
+    impl Thing for String {
+        fn id(&self) -> &str {
+            self
+        }
+    }
+
+    fn page(items: &[&str], after: Option<&str>) -> Page<String> {
+        Page {
+            items: items.iter().map(ToString::to_string).collect(),
+            after: after.map(str::to_string),
+            before: None,
+            horizon: false,
+            omitted: 0,
+        }
+    }
+
+    #[test]
+    fn test_from_listing() {
+        let json = r#"{"kind": "Listing", "data": {"after": "t3_b", "before": null, "children": [
+            {"kind": "t3", "data": "a"}, {"kind": "t3", "data": "b"}
+        ]}}"#;
+        let listing: Generic<String> = serde_json::from_str(json).unwrap();
+        let path = Path::new("r/rust/new.json");
+
+        let page = Page::from_listing(listing.clone(), "link", path).unwrap();
+        assert_eq!(page.items, ["a", "b"]);
+        assert_eq!(page.after.as_deref(), Some("t3_b"));
+        assert!(!page.is_last());
+
+        assert!(Page::from_listing(listing, "comment", path).is_err());
+    }
+
+    #[test]
+    fn test_omitted() {
+        let json = r#"{"kind": "Listing", "data": {"after": null, "before": null, "children": [
+            {"kind": "t1", "data": "a"},
+            {"kind": "more", "data": {
+                "id": "m", "name": "t1_m", "parent_id": "t3_p", "count": 12,
+                "children": ["b", "c"]
+            }}
+        ]}}"#;
+        let listing: Generic<String> = serde_json::from_str(json).unwrap();
+
+        let page = Page::from_listing(listing, "comment", Path::new("r/rust/comments/p.json"));
+        let page = page.unwrap();
+        assert_eq!(page.items, ["a"]);
+        assert_eq!(page.omitted, 12);
+    }
+
+    #[test]
+    fn test_advance() {
+        let client = Client::new("test");
+        let mut paginator: Paginator<_, String> = Paginator::new(
+            client,
+            "r/rust/new.json".into(),
+            Vec::new(),
+            Options::new(),
+            Shape::Listing { kind: "link" },
+        )
+        .max_items(3);
+
+        let first = paginator.advance(page(&["a", "b"], Some("t3_b")));
+        assert_eq!(first.items.len(), 2);
+        assert!(!paginator.done);
+
+        // the listing shifted, and we only want one more.
+        let second = paginator.advance(page(&["b", "c", "d"], Some("t3_d")));
+        assert_eq!(second.items, ["c"]);
+        assert_eq!(paginator.seen, 3);
+        assert!(paginator.fetch().is_none());
+    }
+
+    #[test]
+    fn test_backwards() {
+        let mut paginator: Paginator<_, String> = Paginator::new(
+            Client::new("test"),
+            "r/rust/new.json".into(),
+            Vec::new(),
+            Options::new().before("t3_x").count(300),
+            Shape::Listing { kind: "link" },
+        );
+
+        let mut newer = page(&["c", "d"], Some("t3_d"));
+        newer.before = Some("t3_c".to_string());
+        let first = paginator.advance(newer.clone());
+        assert_eq!(first.items, ["c", "d"]);
+        assert!(!paginator.done);
+        assert!(paginator.options.backwards());
+
+        // the page after it comes before it in the listing.
+        let mut newest = page(&["a", "b"], Some("t3_b"));
+        newest.before = Some("t3_a".to_string());
+        let mut ids = ["c", "d"].map(ToString::to_string).into();
+        newer.join(newest, true, &mut ids);
+        assert_eq!(newer.items, ["a", "b", "c", "d"]);
+        assert_eq!(newer.before.as_deref(), Some("t3_a"));
+        assert_eq!(newer.after.as_deref(), Some("t3_d"));
+
+        // there's nothing newer than the front page.
+        let last = paginator.advance(page(&["a", "b"], Some("t3_b")));
+        assert!(!last.horizon);
+        assert!(paginator.done);
+    }
+
+    #[tokio::test]
+    async fn test_zero_limit() {
+        let client = Client::new("test");
+        let path = Path::new("r/rust/new.json");
+
+        // without asking Reddit, which would send 25 items.
+        let page: Page<String> = fetch_listing(&client, path, &[], Options::new().limit(0), "link")
+            .await
+            .unwrap();
+        assert!(page.items.is_empty());
+
+        let paginator: Paginator<_, String> = Paginator::new(
+            client,
+            path.into(),
+            Vec::new(),
+            Options::new().limit(0),
+            Shape::Listing { kind: "link" },
+        );
+        assert!(paginator.fetch().is_none());
+    }
+
+    #[test]
+    fn test_horizon() {
+        let mut paginator: Paginator<_, String> = Paginator::new(
+            Client::new("test"),
+            "r/rust/new.json".into(),
+            Vec::new(),
+            Options::new().count(950),
+            Shape::Listing { kind: "link" },
+        );
+
+        let last = paginator.advance(page(&["a", "b"], None));
+        assert!(last.horizon);
+        assert!(paginator.done);
+
+        let mut paginator: Paginator<_, String> = Paginator::new(
+            Client::new("test"),
+            "r/rust/new.json".into(),
+            Vec::new(),
+            Options::new(),
+            Shape::Listing { kind: "link" },
+        );
+
+        // a small subreddit just runs out.
+        let last = paginator.advance(page(&["a", "b"], None));
+        assert!(!last.horizon);
+    }
+}
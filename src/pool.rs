@@ -0,0 +1,467 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use reqwest::{Method, StatusCode};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use tracing::debug;
+
+use crate::auth::{self, Authenticator, Scope};
+use crate::errors::{Context, RedditError};
+#[cfg(feature = "stream")]
+use crate::subreddit::multistream::{Storage, StreamBuilder};
+use crate::{build_url, Body, Client, Error, RateLimit, Result, Subreddit};
+
+/// How long a member that failed to authenticate sits out before we try it again.
+const COOLDOWN: Duration = Duration::from_mins(1);
+
+// internal plumbing, which must stay out of the public API even if `pool` is made public.
+#[allow(clippy::redundant_pub_crate)]
+pub(crate) type Shared<A> = Arc<Members<A>>;
+
+/// A pool of [`Client`]s, usually logged in to different accounts, which share the work.
+///
+/// Every request goes to the member with the most budget left, according to the
+/// `X-Ratelimit-*` headers Reddit sends back. If a member is rate limited or can't
+/// authenticate, the request is retried on the next one.
+///
+/// # Example
+/// ```no_run
+/// use reddit_api::{auth, Client, ClientPool};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), reddit_api::Error> {
+/// let mut clients = Vec::new();
+/// for (username, password) in [("first", "hunter2"), ("second", "hunter3")] {
+///     let auth = auth::Password::new("client_id", "client_secret", username, password);
+///     clients.push(Client::new("my-bot (by u/username)").login(auth).await?);
+/// }
+///
+/// let pool = ClientPool::new(clients);
+/// let latest = pool.subreddit("rust").latest().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ClientPool<A: Authenticator> {
+    members: Shared<A>,
+}
+
+#[allow(clippy::redundant_pub_crate)]
+pub(crate) struct Members<A: Authenticator> {
+    members: Vec<Member<A>>,
+}
+
+struct Member<A: Authenticator> {
+    client: Client<A>,
+    budget: Mutex<Budget>,
+}
+
+/// A request picked to be sent by a member, which is in flight until this is dropped, even
+/// if the request is cancelled halfway through.
+struct InFlight<'a, A: Authenticator> {
+    member: &'a Member<A>,
+}
+
+/// What we know about a member, besides its rate limit window.
+#[derive(Debug, Default)]
+struct Budget {
+    /// Requests sent but not answered yet.
+    in_flight: usize,
+    /// Don't send anything until then, the member failed to authenticate.
+    unhealthy_until: Option<Instant>,
+}
+
+impl<A: Authenticator> ClientPool<A> {
+    /// Creates a new [`ClientPool`] out of `clients`.
+    ///
+    /// # Panics
+    /// This method panics if `clients` is empty.
+    #[must_use]
+    pub fn new<I: IntoIterator<Item = Client<A>>>(clients: I) -> Self {
+        let members: Vec<_> = clients
+            .into_iter()
+            .map(|mut client| {
+                // members send their requests themselves.
+                client.pool = None;
+                // and hand 429s back, so we can try the next one instead of waiting.
+                let settings = Arc::make_mut(&mut client.settings);
+                settings.retry = settings.retry.clone().without_rate_limited();
+
+                Member {
+                    client,
+                    budget: Mutex::default(),
+                }
+            })
+            .collect();
+
+        assert!(!members.is_empty(), "a pool needs at least one client");
+
+        Self {
+            members: Arc::new(Members { members }),
+        }
+    }
+
+    /// Returns a [`Client`] which sends its requests through this [`ClientPool`].
+    ///
+    /// Its [`Client::rate_limit`] is the one of the whole pool. It's otherwise a clone of the
+    /// first member, so [`Client::base_url`] is where that one sends its requests, though
+    /// every member sends them to its own.
+    #[must_use]
+    pub fn client(&self) -> Client<A> {
+        let mut client = self.members.members[0].client.clone();
+        client.pool = Some(Arc::clone(&self.members));
+        client
+    }
+
+    /// Returns the rate limit windows of every member, added up.
+    #[must_use]
+    pub fn rate_limit(&self) -> RateLimit {
+        self.members.rate_limit()
+    }
+
+    /// Create a new [`Subreddit`] instance, which sends its requests through this
+    /// [`ClientPool`].
+    #[must_use]
+    pub fn subreddit(&self, subreddit: &str) -> Subreddit<A> {
+        self.client().subreddit(subreddit)
+    }
+
+    /// Creates a new [`StreamBuilder`] with `subreddits` added, which sends its requests
+    /// through this [`ClientPool`].
+    #[cfg(feature = "stream")]
+    #[doc(cfg(feature = "stream"))]
+    #[must_use = "builder does nothing unless built"]
+    pub fn stream<'a, S, I>(&self, subreddits: I, storage: S) -> StreamBuilder<A, S>
+    where
+        S: Storage + Clone,
+        I: IntoIterator<Item = &'a str>,
+    {
+        let client = self.client();
+
+        StreamBuilder::new()
+            .add_subs(subreddits.into_iter().map(|name| client.subreddit(name)))
+            .set_storage(storage)
+    }
+}
+
+impl<A: Authenticator> Members<A> {
+    /// Returns the rate limit windows of every member, added up.
+    pub(crate) fn rate_limit(&self) -> RateLimit {
+        let limits = self.members.iter().map(|member| member.client.rate_limit());
+        RateLimit::combine(limits, Instant::now())
+    }
+
+    /// Sends a `GET` request to `path` through the member with the most budget left,
+    /// failing over to the next one if it's rate limited or can't authenticate.
+    ///
+    /// See [`Client::parse_get`] for `subreddit`.
+    pub(crate) async fn get_json<T: DeserializeOwned>(
+        &self,
+        scope: Scope,
+        path: &Path,
+        params: &[(&str, String)],
+        subreddit: bool,
+    ) -> Result<T> {
+        let mut tried = vec![false; self.members.len()];
+        let mut last_err = None;
+
+        while let Some((i, in_flight)) = self.pick(&mut tried) {
+            let member = in_flight.member;
+            let res = member
+                .client
+                .send(Method::GET, scope, path, params, None)
+                .await;
+
+            match (member.record(i, &res), res) {
+                (true, Ok(resp)) => {
+                    last_err = Client::<A>::parse_get::<T>(resp, subreddit).await.err();
+                }
+                (false, Ok(resp)) => return Client::<A>::parse_get(resp, subreddit).await,
+                (true, Err(e)) => last_err = Some(e),
+                (false, Err(e)) => return Err(e),
+            }
+        }
+
+        // every member was out of budget before we sent anything.
+        Err(last_err.unwrap_or_else(|| {
+            let url = build_url(self.members[0].client.base_url.clone(), path, params);
+            let context = Context::new(Method::GET, &url, StatusCode::TOO_MANY_REQUESTS);
+            Error::api(RedditError::RateLimited, context)
+        }))
+    }
+
+    /// Sends a `method` request to `path` with `body` through every member, one after the
+    /// other, so they all see the change.
+    ///
+    /// Returns what the first member which applied it got back, or [`Error::Pool`] if any of
+    /// them failed to, even though the rest did.
+    pub(crate) async fn write_json<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        scope: Scope,
+        path: &Path,
+        body: &Body<'_>,
+    ) -> Result<T> {
+        let mut res = None;
+        let mut applied = Vec::new();
+        let mut failed = Vec::new();
+
+        for (i, member) in self.members.iter().enumerate() {
+            let sent = member
+                .client
+                .send(method.clone(), scope, path, &[], Some(body))
+                .await;
+
+            let parsed = match sent {
+                Ok(resp) if res.is_none() => Client::<A>::parse(method.clone(), resp)
+                    .await
+                    .map(|parsed| res = Some(parsed)),
+                Ok(resp) => Client::<A>::parse::<IgnoredAny>(method.clone(), resp)
+                    .await
+                    .map(drop),
+                Err(e) => Err(e),
+            };
+
+            match parsed {
+                Ok(()) => applied.push(i),
+                Err(e) => {
+                    debug!(member = i, error = %e, "couldn't apply the change");
+                    failed.push((i, e));
+                }
+            }
+        }
+
+        match res {
+            Some(res) if failed.is_empty() => Ok(res),
+            _ => Err(Error::Pool { applied, failed }),
+        }
+    }
+
+    /// Picks the available member with the most budget left, and the fewest requests in
+    /// flight, which hasn't been `tried` yet.
+    fn pick(&self, tried: &mut [bool]) -> Option<(usize, InFlight<'_, A>)> {
+        let now = Instant::now();
+
+        let (i, ..) = self
+            .members
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !tried[*i])
+            .filter_map(|(i, member)| {
+                let remaining = member.client.rate_limit().remaining_at(now);
+                let budget = member.budget();
+                let available = remaining >= 1.0 && budget.is_healthy(now);
+
+                available.then_some((i, remaining, budget.in_flight))
+            })
+            .min_by(|a, b| b.1.total_cmp(&a.1).then(a.2.cmp(&b.2)))?;
+
+        tried[i] = true;
+
+        let member = &self.members[i];
+        member.budget().in_flight += 1;
+
+        Some((i, InFlight { member }))
+    }
+}
+
+impl<A: Authenticator> Member<A> {
+    fn budget(&self) -> MutexGuard<'_, Budget> {
+        self.budget.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Records the outcome of a request sent by the `i`th member, returning `true` if it
+    /// should be sent by another one.
+    fn record(&self, i: usize, res: &Result<reqwest::Response>) -> bool {
+        let now = Instant::now();
+        let mut budget = self.budget();
+
+        let failover = match res {
+            // the member's limiter already knows it's out of budget.
+            Ok(resp) => {
+                budget.unhealthy_until = None;
+
+                match resp.status() {
+                    StatusCode::TOO_MANY_REQUESTS => {
+                        debug!(member = i, "rate limited, trying the next client");
+                        true
+                    }
+                    StatusCode::UNAUTHORIZED => {
+                        budget.unhealthy_until = Some(now + COOLDOWN);
+                        debug!(member = i, "unauthorized, trying the next client");
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            // another member may have been granted the scope.
+            Err(Error::AuthError(auth::Error::MissingScope(_))) => true,
+            Err(
+                e @ Error::AuthError(
+                    auth::Error::LoggedOut
+                    | auth::Error::NeedsRefresh
+                    | auth::Error::NoRefreshToken
+                    | auth::Error::Token(_),
+                ),
+            ) => {
+                budget.unhealthy_until = Some(now + COOLDOWN);
+                debug!(member = i, error = %e, "can't authenticate, trying the next client");
+                true
+            }
+            Err(_) => false,
+        };
+        drop(budget);
+
+        failover
+    }
+}
+
+impl<A: Authenticator> Drop for InFlight<'_, A> {
+    fn drop(&mut self) {
+        self.member.budget().in_flight -= 1;
+    }
+}
+
+impl Budget {
+    /// Returns `false` if the member failed to authenticate recently.
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.unhealthy_until.is_none_or(|until| until <= now)
+    }
+}
+
+impl<A: Authenticator> std::fmt::Debug for ClientPool<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientPool")
+            .field("members", &self.members.members.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use reqwest::StatusCode;
+
+    use super::{Budget, ClientPool};
+    use crate::auth::{self, Scope};
+    use crate::{Client, Error};
+
+    fn headers(remaining: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static(remaining));
+        headers.insert("x-ratelimit-reset", HeaderValue::from_static("60"));
+        headers
+    }
+
+    #[test]
+    fn test_budget_health() {
+        let now = Instant::now();
+        let budget = Budget {
+            unhealthy_until: Some(now + Duration::from_secs(10)),
+            ..Budget::default()
+        };
+
+        assert!(!budget.is_healthy(now));
+        assert!(budget.is_healthy(now + Duration::from_secs(11)));
+    }
+
+    #[test]
+    fn test_pick() {
+        let pool = ClientPool::new([Client::new("a"), Client::new("b"), Client::new("c")]);
+        let members = &pool.members;
+
+        members.members[0]
+            .client
+            .rate_limit
+            .record(StatusCode::OK, &headers("10"));
+        members.members[1]
+            .client
+            .rate_limit
+            .record(StatusCode::OK, &headers("0"));
+        members.members[2]
+            .client
+            .rate_limit
+            .record(StatusCode::OK, &headers("20"));
+
+        let mut tried = vec![false; 3];
+        let (first, in_flight) = members.pick(&mut tried).unwrap();
+        assert_eq!(first, 2);
+        assert_eq!(members.pick(&mut tried).map(|(i, _)| i), Some(0));
+        // the last one is out of budget.
+        assert!(members.pick(&mut tried).is_none());
+
+        assert_eq!(members.members[2].budget().in_flight, 1);
+        // cancelled or not, the request is done.
+        drop(in_flight);
+        assert_eq!(members.members[2].budget().in_flight, 0);
+    }
+
+    #[test]
+    fn test_pool_rate_limit() {
+        let pool = ClientPool::new([Client::new("a"), Client::new("b")]);
+
+        for member in &pool.members.members {
+            member
+                .client
+                .rate_limit
+                .record(StatusCode::OK, &headers("10"));
+        }
+
+        assert_eq!(pool.rate_limit().remaining(), Some(20.0));
+        assert_eq!(pool.client().rate_limit(), pool.rate_limit());
+    }
+
+    #[test]
+    fn test_members_fail_over_429() {
+        let pool = ClientPool::new([Client::new("a"), Client::new("b")]);
+        let retry = &pool.members.members[0].client.settings.retry;
+
+        assert!(!retry.is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        // the client it was made from still retries them.
+        assert!(Client::new("c")
+            .settings
+            .retry
+            .is_retryable(StatusCode::TOO_MANY_REQUESTS));
+    }
+
+    #[tokio::test]
+    async fn test_write_every_member() {
+        let pool = ClientPool::new([Client::new("a"), Client::new("b")]);
+
+        // anonymous clients can't change preferences, so none of them apply it.
+        let res = pool.client().set_over_18(true).await;
+        let Err(Error::Pool { applied, failed }) = res else {
+            panic!("expected a pool error, got {res:?}");
+        };
+
+        assert!(applied.is_empty());
+        assert_eq!(failed.iter().map(|(i, _)| *i).collect::<Vec<_>>(), [0, 1]);
+        assert!(failed.iter().all(|(_, e)| matches!(
+            e,
+            Error::AuthError(auth::Error::MissingScope(Scope::Account))
+        )));
+    }
+
+    #[test]
+    fn test_pick_least_loaded() {
+        let pool = ClientPool::new([Client::new("a"), Client::new("b")]);
+        let members = &pool.members;
+
+        let _busy = members.pick(&mut [false, true]).unwrap();
+
+        assert_eq!(members.pick(&mut [false, false]).map(|(i, _)| i), Some(1));
+    }
+
+    #[test]
+    fn test_pick_skips_unhealthy() {
+        let pool = ClientPool::new([Client::new("a"), Client::new("b")]);
+        let members = &pool.members;
+
+        members.members[0].budget().unhealthy_until = Some(Instant::now() + Duration::from_mins(1));
+
+        assert_eq!(members.pick(&mut [false, false]).map(|(i, _)| i), Some(1));
+    }
+}
@@ -0,0 +1,263 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use tracing::debug;
+
+/// How long we assume a window lasts, if Reddit rate limits us without saying.
+const DEFAULT_RESET: Duration = Duration::from_mins(1);
+
+/// A snapshot of a [`crate::Client`]'s rate limit window, from the `X-Ratelimit-*` headers
+/// of the last response.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RateLimit {
+    remaining: Option<f64>,
+    used: Option<u64>,
+    reset_at: Option<Instant>,
+}
+
+impl RateLimit {
+    /// Returns how many requests are left in the current window, if Reddit told us.
+    ///
+    /// Requests sent since the last response are already taken out.
+    #[must_use]
+    pub const fn remaining(&self) -> Option<f64> {
+        self.remaining
+    }
+
+    /// Returns how many requests were used in the current window, if Reddit told us.
+    #[must_use]
+    pub const fn used(&self) -> Option<u64> {
+        self.used
+    }
+
+    /// Returns when the current window resets, if Reddit told us.
+    #[must_use]
+    pub const fn reset_at(&self) -> Option<Instant> {
+        self.reset_at
+    }
+
+    /// Returns how long until the current window resets, if Reddit told us.
+    #[must_use]
+    pub fn reset_in(&self) -> Option<Duration> {
+        self.reset_at
+            .map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Returns `true` if there are no requests left until the window resets.
+    #[must_use]
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining_at(Instant::now()) < 1.0
+    }
+
+    /// Returns the requests left at `now`, which is unlimited as far as we know if Reddit
+    /// didn't tell us or the window has reset since.
+    pub(crate) fn remaining_at(&self, now: Instant) -> f64 {
+        match (self.remaining, self.reset_at) {
+            (Some(remaining), Some(reset_at)) if reset_at > now => remaining,
+            (Some(remaining), None) => remaining,
+            _ => f64::INFINITY,
+        }
+    }
+
+    /// Adds up the windows of a [`crate::ClientPool`]'s members, as of `now`.
+    ///
+    /// The pool has as many requests left as its members put together, which we don't know
+    /// if we don't for any one of them, and its window resets as soon as one of theirs does.
+    pub(crate) fn combine<I: IntoIterator<Item = Self>>(limits: I, now: Instant) -> Self {
+        let mut combined = Self::default();
+        let mut remaining = Some(0.0);
+
+        for limit in limits {
+            let left = limit.remaining_at(now);
+            remaining = remaining.filter(|_| left.is_finite()).map(|sum| sum + left);
+
+            // windows which have reset since don't count.
+            if limit.reset_at.is_some_and(|at| at <= now) {
+                continue;
+            }
+            combined.used = match (combined.used, limit.used) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            };
+            combined.reset_at = match (combined.reset_at, limit.reset_at) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        }
+
+        combined.remaining = remaining;
+        combined
+    }
+}
+
+/// Keeps track of the [`RateLimit`] of a [`crate::Client`] and all of its clones.
+#[derive(Debug, Default)]
+pub struct Limiter {
+    state: Mutex<RateLimit>,
+    /// Whether to wait for the window to reset instead of sending requests we know will
+    /// be rate limited.
+    throttle: bool,
+}
+
+impl Limiter {
+    pub fn new(throttle: bool) -> Self {
+        Self {
+            state: Mutex::default(),
+            throttle,
+        }
+    }
+
+    pub fn snapshot(&self) -> RateLimit {
+        *self.state()
+    }
+
+    /// Takes a request out of the window, first waiting for it to reset if it's exhausted
+    /// and we're throttling.
+    pub async fn acquire(&self) {
+        while let Some(wait) = self.reserve() {
+            debug!(
+                ?wait,
+                "rate limit exhausted, waiting for the window to reset"
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Takes a request out of the window, or returns how long to wait for it to reset.
+    fn reserve(&self) -> Option<Duration> {
+        let now = Instant::now();
+        let mut state = self.state();
+
+        if self.throttle && state.remaining_at(now) < 1.0 {
+            return state.reset_at.map(|at| at.saturating_duration_since(now));
+        }
+
+        if let Some(ref mut remaining) = state.remaining {
+            *remaining -= 1.0;
+        }
+        drop(state);
+
+        None
+    }
+
+    /// Records the `X-Ratelimit-*` headers of a response.
+    pub fn record(&self, status: StatusCode, headers: &HeaderMap) {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<f64>().ok())
+        };
+
+        let now = Instant::now();
+        let mut state = self.state();
+
+        if let Some(remaining) = header("x-ratelimit-remaining") {
+            state.remaining = Some(remaining);
+        }
+        if let Some(used) = header("x-ratelimit-used") {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let used = used.max(0.0) as u64;
+            state.used = Some(used);
+        }
+        if let Some(reset) = header("x-ratelimit-reset") {
+            state.reset_at = Some(now + Duration::from_secs_f64(reset.max(0.0)));
+        }
+
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            state.remaining = Some(0.0);
+            if state.reset_at.is_none_or(|at| at <= now) {
+                state.reset_at = Some(now + DEFAULT_RESET);
+            }
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, RateLimit> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use reqwest::StatusCode;
+
+    use super::{Limiter, RateLimit};
+
+    fn headers(remaining: &'static str, used: &'static str, reset: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static(remaining));
+        headers.insert("x-ratelimit-used", HeaderValue::from_static(used));
+        headers.insert("x-ratelimit-reset", HeaderValue::from_static(reset));
+        headers
+    }
+
+    #[test]
+    fn test_record() {
+        let limiter = Limiter::default();
+        assert_eq!(limiter.snapshot(), RateLimit::default());
+
+        limiter.record(StatusCode::OK, &headers("598.0", "2", "540"));
+        let rate_limit = limiter.snapshot();
+
+        assert_eq!(rate_limit.remaining(), Some(598.0));
+        assert_eq!(rate_limit.used(), Some(2));
+        assert!(rate_limit.reset_in().unwrap() > Duration::from_secs(530));
+        assert!(!rate_limit.is_exhausted());
+
+        // once the window resets, we don't know anymore.
+        let later = Instant::now() + Duration::from_secs(541);
+        assert!(rate_limit.remaining_at(later).is_infinite());
+    }
+
+    #[test]
+    fn test_rate_limited() {
+        let limiter = Limiter::default();
+        limiter.record(StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new());
+
+        let rate_limit = limiter.snapshot();
+        assert!(rate_limit.is_exhausted());
+        assert!(rate_limit.reset_in().is_some());
+    }
+
+    #[test]
+    fn test_combine() {
+        let now = Instant::now();
+        let first = Limiter::default();
+        first.record(StatusCode::OK, &headers("10.0", "590", "60"));
+        let second = Limiter::default();
+        second.record(StatusCode::OK, &headers("20.0", "580", "30"));
+
+        let combined = RateLimit::combine([first.snapshot(), second.snapshot()], now);
+        assert_eq!(combined.remaining(), Some(30.0));
+        assert_eq!(combined.used(), Some(1170));
+        assert!(combined.reset_in().unwrap() <= Duration::from_secs(30));
+
+        // one we don't know about could have any number left.
+        let combined = RateLimit::combine([first.snapshot(), RateLimit::default()], now);
+        assert_eq!(combined.remaining(), None);
+        assert_eq!(combined.used(), Some(590));
+    }
+
+    #[tokio::test]
+    async fn test_acquire() {
+        let limiter = Limiter::new(true);
+        limiter.record(StatusCode::OK, &headers("2.0", "598", "600"));
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert!(limiter.snapshot().is_exhausted());
+
+        // the window resets right away, so we don't have to wait long.
+        *limiter.state() = RateLimit {
+            remaining: Some(0.0),
+            used: None,
+            reset_at: Some(Instant::now() + Duration::from_millis(10)),
+        };
+        limiter.acquire().await;
+    }
+}
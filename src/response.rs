@@ -1,3 +1,5 @@
+use crate::subreddit::comments::MoreComments;
+
 /// [`RedditUrl`] represents a `Url` inside of Reddit.
 ///
 /// This is needed since [`crate::Submission.url`] may link to another [`crate::Submission`], in which case it only contains
@@ -27,6 +29,10 @@ pub enum Generic<T> {
     LabeledMulti {
         data: T,
     },
+    #[serde(rename = "more")]
+    More {
+        data: MoreComments,
+    },
 }
 
 #[derive(Debug, Clone, serde::Deserialize)]
@@ -48,12 +54,13 @@ impl<T> IntoIterator for Listing<T> {
 }
 
 impl<T> Generic<T> {
-    pub const fn kind_name(&self) -> &str {
+    pub const fn kind_name(&self) -> &'static str {
         match self {
             Self::Listing { .. } => "listing",
             Self::Link { .. } => "link",
             Self::Comment { .. } => "comment",
             Self::LabeledMulti { .. } => "multi",
+            Self::More { .. } => "more",
         }
     }
 }
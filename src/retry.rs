@@ -0,0 +1,239 @@
+use std::time::Duration;
+
+use nanorand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+
+/// How a [`crate::Client`] retries `GET` requests which failed for reasons that may go away,
+/// like a `503` or a connection reset.
+///
+/// Waits grow exponentially from [`RetryPolicy::base_delay`] up to
+/// [`RetryPolicy::max_delay`], unless Reddit tells us how long to wait with a `Retry-After`
+/// or `X-Ratelimit-Reset` header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    /// Whether `429`s are retried, instead of handed back to a [`crate::ClientPool`] which
+    /// can send the request through another account.
+    rate_limited: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            rate_limited: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A [`RetryPolicy`] which never retries.
+    #[must_use]
+    pub fn never() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// Sets how many times a request is sent at most, counting the first one. Defaults to 3.
+    #[must_use]
+    pub const fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets how long to wait before the first retry. Defaults to 500 milliseconds.
+    #[must_use]
+    pub const fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Sets the longest we wait before a retry, even if Reddit asks for longer.
+    /// Defaults to 30 seconds.
+    #[must_use]
+    pub const fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Sets whether to randomize waits, so clients which failed together don't retry
+    /// together. Defaults to `true`.
+    #[must_use]
+    pub const fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Stops retrying `429`s, for members of a [`crate::ClientPool`], which rather try
+    /// another account than wait for this one's window to reset.
+    #[must_use]
+    pub(crate) const fn without_rate_limited(mut self) -> Self {
+        self.rate_limited = false;
+        self
+    }
+
+    /// Returns `true` if a request which got `status` back is worth retrying.
+    pub(crate) fn is_retryable(&self, status: StatusCode) -> bool {
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => self.rate_limited,
+            StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if a request which failed with `error` is worth retrying.
+    pub(crate) fn is_retryable_error(error: &reqwest::Error) -> bool {
+        error.is_connect() || error.is_timeout() || error.is_request()
+    }
+
+    /// Returns how long to wait before sending the request again, after `attempt` failed,
+    /// or `None` if we're out of attempts.
+    pub(crate) fn delay(
+        &self,
+        attempt: u32,
+        status: Option<StatusCode>,
+        headers: Option<&HeaderMap>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        let asked = headers.and_then(|headers| {
+            let seconds = |name| {
+                headers
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse::<f64>().ok())
+                    .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+                    .map(Duration::from_secs_f64)
+            };
+
+            seconds(RETRY_AFTER.as_str()).or_else(|| {
+                (status == Some(StatusCode::TOO_MANY_REQUESTS))
+                    .then(|| seconds("x-ratelimit-reset"))
+                    .flatten()
+            })
+        });
+
+        Some(
+            asked
+                .unwrap_or_else(|| self.backoff(attempt))
+                .min(self.max_delay),
+        )
+    }
+
+    /// Returns the exponential backoff after `attempt` failed, with half of it randomized if
+    /// [`RetryPolicy::jitter`] is set.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+
+        if !self.jitter {
+            return delay;
+        }
+
+        let half = delay / 2;
+        half + half.mul_f64(nanorand::tls_rng().generate())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+    use reqwest::StatusCode;
+
+    use super::RetryPolicy;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::new().max_attempts(5).jitter(false);
+
+        assert_eq!(
+            policy.delay(1, None, None),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(policy.delay(2, None, None), Some(Duration::from_secs(1)));
+        assert_eq!(policy.delay(4, None, None), Some(Duration::from_secs(4)));
+        assert_eq!(policy.delay(5, None, None), None);
+
+        let capped = policy.max_attempts(20).max_delay(Duration::from_secs(2));
+        assert_eq!(capped.delay(10, None, None), Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_jitter() {
+        let policy = RetryPolicy::new().max_attempts(5);
+
+        for _ in 0..100 {
+            let delay = policy.delay(3, None, None).unwrap();
+            assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_secs(2));
+        }
+    }
+
+    #[test]
+    fn test_headers() {
+        let policy = RetryPolicy::new().jitter(false);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-reset", HeaderValue::from_static("7"));
+
+        // the reset header only matters when we're rate limited.
+        let status = Some(StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            policy.delay(1, status, Some(&headers)),
+            Some(Duration::from_secs(7))
+        );
+        let status = Some(StatusCode::BAD_GATEWAY);
+        assert_eq!(
+            policy.delay(1, status, Some(&headers)),
+            Some(Duration::from_millis(500))
+        );
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3"));
+        assert_eq!(
+            policy.delay(1, status, Some(&headers)),
+            Some(Duration::from_secs(3))
+        );
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3600"));
+        assert_eq!(
+            policy.delay(1, status, Some(&headers)),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn test_never() {
+        assert_eq!(RetryPolicy::never().delay(1, None, None), None);
+        let policy = RetryPolicy::new();
+        assert!(!policy.is_retryable(StatusCode::NOT_FOUND));
+        assert!(policy.is_retryable(StatusCode::SERVICE_UNAVAILABLE));
+    }
+
+    #[test]
+    fn test_rate_limited() {
+        let policy = RetryPolicy::new();
+        assert!(policy.is_retryable(StatusCode::TOO_MANY_REQUESTS));
+
+        // pool members hand 429s back, so the pool tries another account.
+        let member = policy.without_rate_limited();
+        assert!(!member.is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(member.is_retryable(StatusCode::BAD_GATEWAY));
+    }
+}
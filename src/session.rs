@@ -0,0 +1,308 @@
+use std::sync::Arc;
+#[cfg(not(feature = "shared_auth"))]
+use std::sync::{PoisonError, RwLock, RwLockReadGuard};
+use std::time::Duration;
+
+use tracing::debug;
+
+use crate::auth::{self, Authenticator, Scope};
+
+/// The [`Authenticator`] of a [`crate::Client`], which logs in again before its token
+/// expires.
+///
+/// With the `shared_auth` feature enabled, every clone of a [`crate::Client`] shares it.
+/// Otherwise each clone gets a copy of its own, which it refreshes on its own.
+pub struct Session<A> {
+    #[cfg(feature = "shared_auth")]
+    auth: Arc<tokio::sync::RwLock<A>>,
+    #[cfg(not(feature = "shared_auth"))]
+    auth: RwLock<A>,
+    /// Held while logging in again, so concurrent requests share a single refresh.
+    #[cfg(not(feature = "shared_auth"))]
+    refresh: tokio::sync::Mutex<()>,
+    /// Shared by every clone, which all start out with the same token.
+    #[cfg(not(feature = "shared_auth"))]
+    handles: Arc<()>,
+}
+
+impl<A: Authenticator> Session<A> {
+    pub fn new(auth: A) -> Self {
+        Self {
+            #[cfg(feature = "shared_auth")]
+            auth: Arc::new(tokio::sync::RwLock::new(auth)),
+            #[cfg(not(feature = "shared_auth"))]
+            auth: RwLock::new(auth),
+            #[cfg(not(feature = "shared_auth"))]
+            refresh: tokio::sync::Mutex::new(()),
+            #[cfg(not(feature = "shared_auth"))]
+            handles: Arc::new(()),
+        }
+    }
+
+    /// Authenticates `req`, which requires `scope`, logging in again first if the token
+    /// expires within `margin`.
+    pub async fn authorize(
+        &self,
+        client: &reqwest::Client,
+        margin: Duration,
+        scope: Scope,
+        req: reqwest::RequestBuilder,
+    ) -> auth::Result<reqwest::RequestBuilder> {
+        self.refresh_if_needed(client, margin).await?;
+
+        #[cfg(feature = "shared_auth")]
+        let guard = self.auth.read().await;
+        #[cfg(not(feature = "shared_auth"))]
+        let guard = self.read();
+
+        if !guard.has_scope(scope) {
+            return Err(auth::Error::MissingScope(scope));
+        }
+        guard.auth_request(req)
+    }
+
+    /// Logs in again if the token expires within `margin`.
+    ///
+    /// Concurrent callers queue up on a lock, so only the first one refreshes the token and
+    /// the rest see the new one.
+    #[cfg(feature = "shared_auth")]
+    async fn refresh_if_needed(
+        &self,
+        client: &reqwest::Client,
+        margin: Duration,
+    ) -> auth::Result<()> {
+        if !self.auth.read().await.needs_refresh(margin) {
+            return Ok(());
+        }
+
+        let mut guard = self.auth.write().await;
+
+        // someone else may have refreshed it while we were waiting for the lock.
+        if guard.needs_refresh(margin) {
+            debug!("refreshing token");
+            guard.login(client).await?;
+        }
+        drop(guard);
+
+        Ok(())
+    }
+
+    /// Logs in again if the token expires within `margin`.
+    ///
+    /// Concurrent callers queue up on a lock, so only the first one refreshes the token and
+    /// the rest see the new one.
+    #[cfg(not(feature = "shared_auth"))]
+    async fn refresh_if_needed(
+        &self,
+        client: &reqwest::Client,
+        margin: Duration,
+    ) -> auth::Result<()> {
+        if !self.read().needs_refresh(margin) {
+            return Ok(());
+        }
+
+        let _refreshing = self.refresh.lock().await;
+
+        // someone else may have refreshed it while we were waiting for the lock.
+        let mut auth = self.read().clone();
+        if !auth.needs_refresh(margin) {
+            return Ok(());
+        }
+
+        // requests keep using the old token until we have a new one.
+        debug!("refreshing token");
+        auth.login(client).await?;
+        *self.auth.write().unwrap_or_else(PoisonError::into_inner) = auth;
+
+        Ok(())
+    }
+
+    /// Logs in again, whether the token is about to expire or not.
+    #[cfg_attr(feature = "shared_auth", allow(clippy::needless_pass_by_ref_mut))]
+    pub async fn login(&mut self, client: &reqwest::Client) -> auth::Result<()> {
+        #[cfg(feature = "shared_auth")]
+        let res = self.auth.write().await.login(client).await;
+        #[cfg(not(feature = "shared_auth"))]
+        let res = self
+            .auth
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .login(client)
+            .await;
+
+        res
+    }
+
+    /// Logs out of the [`Authenticator`].
+    ///
+    /// # Errors
+    /// Returns [`auth::Error::InUse`] without logging out if there are clones of this
+    /// [`Session`], which would keep using the token.
+    pub async fn logout(&mut self, client: &reqwest::Client) -> auth::Result<()> {
+        #[cfg(feature = "shared_auth")]
+        let auth = Arc::get_mut(&mut self.auth).map(tokio::sync::RwLock::get_mut);
+        #[cfg(not(feature = "shared_auth"))]
+        let auth = Arc::get_mut(&mut self.handles)
+            .map(|()| self.auth.get_mut().unwrap_or_else(PoisonError::into_inner));
+
+        match auth {
+            Some(auth) => auth.logout(client).await,
+            None => Err(auth::Error::InUse),
+        }
+    }
+
+    #[cfg(not(feature = "shared_auth"))]
+    fn read(&self) -> RwLockReadGuard<'_, A> {
+        self.auth.read().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<A: Authenticator> Clone for Session<A> {
+    fn clone(&self) -> Self {
+        #[cfg(feature = "shared_auth")]
+        let session = Self {
+            auth: Arc::clone(&self.auth),
+        };
+        #[cfg(not(feature = "shared_auth"))]
+        let session = Self {
+            handles: Arc::clone(&self.handles),
+            ..Self::new(self.read().clone())
+        };
+
+        session
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::Session;
+    use crate::auth::{self, Authenticator, Scope, Token};
+
+    /// Hands out tokens which last `lifetime`, counting how many times it logged in.
+    #[derive(Clone)]
+    struct Counting {
+        logins: Arc<AtomicUsize>,
+        lifetime: Duration,
+        token: Option<Token>,
+    }
+
+    impl Authenticator for Counting {
+        async fn login(&mut self, _client: &reqwest::Client) -> auth::Result<()> {
+            let n = self.logins.fetch_add(1, Ordering::SeqCst) + 1;
+            self.token = Some(Token::new(n.to_string(), None, self.lifetime));
+            Ok(())
+        }
+
+        async fn logout(&mut self, _client: &reqwest::Client) -> auth::Result<()> {
+            self.token.take().map(drop).ok_or(auth::Error::LoggedOut)
+        }
+
+        fn auth_request(
+            &self,
+            req: reqwest::RequestBuilder,
+        ) -> auth::Result<reqwest::RequestBuilder> {
+            match self.token {
+                Some(ref token) if token.is_expired() => Err(auth::Error::NeedsRefresh),
+                Some(ref token) => Ok(req.bearer_auth(token.as_str())),
+                None => Err(auth::Error::LoggedOut),
+            }
+        }
+
+        fn needs_refresh(&self, margin: Duration) -> bool {
+            self.token
+                .as_ref()
+                .is_some_and(|token| token.expires_within(margin))
+        }
+
+        fn token(&self) -> Option<&Token> {
+            self.token.as_ref()
+        }
+    }
+
+    fn bearer(req: reqwest::RequestBuilder) -> String {
+        let req = req.build().unwrap();
+        req.headers()["authorization"].to_str().unwrap().to_owned()
+    }
+
+    #[tokio::test]
+    async fn test_refresh_expired() {
+        let logins = Arc::new(AtomicUsize::new(0));
+        let session = Session::new(Counting {
+            logins: Arc::clone(&logins),
+            lifetime: Duration::from_hours(1),
+            token: Some(Token::new("expired".into(), None, Duration::ZERO)),
+        });
+
+        let client = reqwest::Client::new();
+        let margin = Duration::from_mins(1);
+        let req = || client.get("http://127.0.0.1/");
+
+        let authorized = session.authorize(&client, margin, Scope::Read, req()).await;
+        assert_eq!(bearer(authorized.unwrap()), "Bearer 1");
+
+        // a fresh token is reused, and so is it by clones.
+        let clone = session.clone();
+        let authorized = clone.authorize(&client, margin, Scope::Read, req()).await;
+        assert_eq!(bearer(authorized.unwrap()), "Bearer 1");
+        assert_eq!(logins.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_once() {
+        let logins = Arc::new(AtomicUsize::new(0));
+        let session = Session::new(Counting {
+            logins: Arc::clone(&logins),
+            lifetime: Duration::from_hours(1),
+            // expires after the margin.
+            token: Some(Token::new("soon".into(), None, Duration::from_secs(30))),
+        });
+
+        let client = reqwest::Client::new();
+        let margin = Duration::from_mins(1);
+        let authorize = || {
+            session.authorize(
+                &client,
+                margin,
+                Scope::Read,
+                client.get("http://127.0.0.1/"),
+            )
+        };
+
+        let (a, b) = tokio::join!(authorize(), authorize());
+        assert_eq!(bearer(a.unwrap()), "Bearer 1");
+        assert_eq!(bearer(b.unwrap()), "Bearer 1");
+        assert_eq!(logins.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_logout_in_use() {
+        let mut session = Session::new(Counting {
+            logins: Arc::new(AtomicUsize::new(0)),
+            lifetime: Duration::from_hours(1),
+            token: Some(Token::new("token".into(), None, Duration::from_hours(1))),
+        });
+
+        let client = reqwest::Client::new();
+        let clone = session.clone();
+
+        // the clone would be left with a revoked token.
+        let res = session.logout(&client).await;
+        assert!(matches!(res, Err(auth::Error::InUse)));
+        let authorized = clone
+            .authorize(
+                &client,
+                Duration::ZERO,
+                Scope::Read,
+                client.get("http://127.0.0.1/"),
+            )
+            .await;
+        assert_eq!(bearer(authorized.unwrap()), "Bearer token");
+
+        drop(clone);
+        session.logout(&client).await.unwrap();
+    }
+}
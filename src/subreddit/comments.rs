@@ -0,0 +1,703 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use reqwest::{Method, StatusCode};
+use tokio::task::JoinSet;
+use tracing::debug;
+
+use crate::auth::{Authenticator, Scope};
+use crate::errors::{Context, JsonError, RedditError};
+use crate::response::{Generic, Listing};
+use crate::{build_url, Error, Result};
+
+use super::feed::CommentOptions;
+use super::submission::{Comment, ThingID};
+use super::Subreddit;
+
+/// How many ids `/api/morechildren` expands at once.
+const MORECHILDREN_BATCH: usize = 100;
+
+/// How many requests [`Subreddit::comments_complete`] has in flight at once.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// A node of a [`CommentTree`], which is either a [`Comment`] or a placeholder for the ones
+/// Reddit didn't send.
+// most nodes are comments, so boxing them would cost more than it saves.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(try_from = "Generic<Comment>")]
+pub enum CommentNode {
+    Comment(Comment),
+    More(MoreComments),
+}
+
+/// A placeholder for comments Reddit left out of a thread, to be fetched separately.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct MoreComments {
+    /// The base36 identifier of this placeholder.
+    pub id: String,
+    /// The fullname of this placeholder, e.g. `t1_2qpqw`.
+    pub name: String,
+    /// The comment or post the missing comments reply to.
+    pub parent_id: ThingID,
+    /// How many comments are missing, including their replies.
+    #[serde(default)]
+    pub count: u64,
+    #[serde(default)]
+    pub depth: u64,
+    /// The base36 identifiers of the missing comments.
+    #[serde(default)]
+    pub children: Vec<String>,
+}
+
+/// The comments of a post, as a tree of [`CommentNode`]s.
+#[derive(Debug, Clone, Default)]
+pub struct CommentTree {
+    /// The top level comments, which reply to the post itself.
+    pub roots: Vec<CommentNode>,
+}
+
+/// Iterates over the nodes of a [`CommentTree`], depth-first.
+#[derive(Debug, Clone)]
+pub struct DepthFirst<'a> {
+    stack: Vec<&'a CommentNode>,
+}
+
+impl CommentNode {
+    /// Returns the [`Comment`], if this isn't a placeholder.
+    #[must_use]
+    pub const fn as_comment(&self) -> Option<&Comment> {
+        match self {
+            Self::Comment(comment) => Some(comment),
+            Self::More(_) => None,
+        }
+    }
+
+    /// Returns the placeholder, if this isn't a [`Comment`].
+    #[must_use]
+    pub const fn as_more(&self) -> Option<&MoreComments> {
+        match self {
+            Self::More(more) => Some(more),
+            Self::Comment(_) => None,
+        }
+    }
+
+    /// Returns the replies to this node, which placeholders don't have.
+    #[must_use]
+    pub fn replies(&self) -> &[Self] {
+        match self {
+            Self::Comment(comment) => &comment.replies,
+            Self::More(_) => &[],
+        }
+    }
+}
+
+impl TryFrom<Generic<Comment>> for CommentNode {
+    type Error = String;
+
+    fn try_from(value: Generic<Comment>) -> Result<Self, Self::Error> {
+        match value {
+            Generic::Comment { data } => Ok(Self::Comment(data)),
+            Generic::More { data } => Ok(Self::More(data)),
+            other => Err(format!("expected a comment, got {}", other.kind_name())),
+        }
+    }
+}
+
+impl MoreComments {
+    /// Returns `true` if this is a "continue this thread" link, which has no ids because
+    /// the thread got too deep, rather than a "load more comments" one.
+    #[must_use]
+    pub const fn is_continue_thread(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+impl CommentTree {
+    /// Iterates over every node of this [`CommentTree`], depth-first, so each [`Comment`]
+    /// comes right before its replies.
+    #[must_use]
+    pub fn iter(&self) -> DepthFirst<'_> {
+        DepthFirst {
+            stack: self.roots.iter().rev().collect(),
+        }
+    }
+
+    /// Iterates over every [`Comment`] of this [`CommentTree`], depth-first.
+    pub fn comments(&self) -> impl Iterator<Item = &Comment> {
+        self.iter().filter_map(CommentNode::as_comment)
+    }
+
+    /// Iterates over every placeholder of this [`CommentTree`], depth-first.
+    pub fn more(&self) -> impl Iterator<Item = &MoreComments> {
+        self.iter().filter_map(CommentNode::as_more)
+    }
+
+    /// Turns this [`CommentTree`] into a list of its [`Comment`]s, depth-first, without
+    /// their replies.
+    #[must_use]
+    pub fn flatten(self) -> Vec<Comment> {
+        let mut comments = Vec::new();
+        let mut stack: Vec<CommentNode> = self.roots.into_iter().rev().collect();
+
+        while let Some(node) = stack.pop() {
+            if let CommentNode::Comment(mut comment) = node {
+                stack.extend(std::mem::take(&mut comment.replies).into_iter().rev());
+                comments.push(comment);
+            }
+        }
+
+        comments
+    }
+
+    /// Finds the [`Comment`] with the base36 identifier `id`.
+    #[must_use]
+    pub fn find(&self, id: &str) -> Option<&Comment> {
+        self.comments().find(|comment| comment.id == id)
+    }
+
+    /// Returns the [`Comment`]s the one with the base36 identifier `id` replies to, starting
+    /// with its parent, or `None` if it's not in this [`CommentTree`].
+    #[must_use]
+    pub fn ancestors(&self, id: &str) -> Option<Vec<&Comment>> {
+        fn path<'a>(nodes: &'a [CommentNode], id: &str, acc: &mut Vec<&'a Comment>) -> bool {
+            for comment in nodes.iter().filter_map(CommentNode::as_comment) {
+                if comment.id == id {
+                    return true;
+                }
+
+                acc.push(comment);
+                if path(&comment.replies, id, acc) {
+                    return true;
+                }
+                acc.pop();
+            }
+
+            false
+        }
+
+        let mut ancestors = Vec::new();
+        path(&self.roots, id, &mut ancestors).then(|| {
+            ancestors.reverse();
+            ancestors
+        })
+    }
+}
+
+impl<'a> IntoIterator for &'a CommentTree {
+    type Item = &'a CommentNode;
+    type IntoIter = DepthFirst<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> Iterator for DepthFirst<'a> {
+    type Item = &'a CommentNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.replies().iter().rev());
+
+        Some(node)
+    }
+}
+
+impl CommentTree {
+    /// Takes every placeholder out of this [`CommentTree`].
+    fn take_more(&mut self) -> Vec<MoreComments> {
+        fn take(nodes: &mut Vec<CommentNode>, out: &mut Vec<MoreComments>) {
+            let (more, mut rest): (Vec<_>, Vec<_>) = std::mem::take(nodes)
+                .into_iter()
+                .partition(|node| matches!(node, CommentNode::More(_)));
+
+            out.extend(more.into_iter().filter_map(|node| match node {
+                CommentNode::More(more) => Some(more),
+                CommentNode::Comment(_) => None,
+            }));
+            for node in &mut rest {
+                if let CommentNode::Comment(comment) = node {
+                    take(&mut comment.replies, out);
+                }
+            }
+
+            *nodes = rest;
+        }
+
+        let mut more = Vec::new();
+        take(&mut self.roots, &mut more);
+        more
+    }
+
+    /// Puts `nodes` under their parents, which are either in this [`CommentTree`] or in
+    /// `nodes` themselves.
+    fn splice(&mut self, nodes: Vec<CommentNode>) {
+        fn attach(nodes: &mut [CommentNode], children: &mut HashMap<ThingID, Vec<CommentNode>>) {
+            for node in nodes {
+                if let CommentNode::Comment(comment) = node {
+                    let id = ThingID::Comment(comment.id.clone());
+                    if let Some(replies) = children.remove(&id) {
+                        comment.replies.extend(replies);
+                    }
+                    attach(&mut comment.replies, children);
+                }
+            }
+        }
+
+        let mut children: HashMap<ThingID, Vec<CommentNode>> = HashMap::new();
+        for node in nodes {
+            children
+                .entry(node.parent_id().clone())
+                .or_default()
+                .push(node);
+        }
+
+        // top level comments reply to the post.
+        let posts: Vec<_> = children
+            .keys()
+            .filter(|parent| matches!(parent, ThingID::Link(_)))
+            .cloned()
+            .collect();
+        for post in posts {
+            self.roots
+                .extend(children.remove(&post).unwrap_or_default());
+        }
+
+        attach(&mut self.roots, &mut children);
+
+        // we'd rather keep the ones we couldn't place than lose them.
+        for (parent, orphans) in children {
+            debug!(
+                %parent,
+                count = orphans.len(),
+                "couldn't find the parent of comments"
+            );
+            self.roots.extend(orphans);
+        }
+    }
+}
+
+impl CommentNode {
+    /// Returns the comment or post this node replies to.
+    const fn parent_id(&self) -> &ThingID {
+        match self {
+            Self::Comment(comment) => &comment.parent_id,
+            Self::More(more) => &more.parent_id,
+        }
+    }
+}
+
+/// A request to fill in some of the comments Reddit left out.
+enum Expansion {
+    /// Comments `/api/morechildren` can expand.
+    Children(Vec<String>),
+    /// The replies to a comment too deep in the thread for `/api/morechildren`.
+    Thread(String),
+}
+
+/// What `/api/morechildren` sends back.
+#[derive(serde::Deserialize)]
+struct MoreChildren {
+    json: MoreChildrenJson,
+}
+
+#[derive(serde::Deserialize)]
+struct MoreChildrenJson {
+    #[serde(default)]
+    errors: Vec<serde_json::Value>,
+    data: Option<MoreChildrenData>,
+}
+
+#[derive(serde::Deserialize)]
+struct MoreChildrenData {
+    things: Vec<CommentNode>,
+}
+
+impl<A> Subreddit<A>
+where
+    A: Authenticator + 'static,
+{
+    /// [`Subreddit::comments_complete`] returns every comment on `article` as a
+    /// [`CommentTree`], expanding the ones Reddit left out with
+    /// [`Subreddit::expand_comments`].
+    ///
+    /// This can take many requests for large threads.
+    ///
+    /// API Calls to: [`/r/{self.name}/comments/{article}.json`] and [`/api/morechildren`]
+    /// # Errors
+    /// Returns `Err` if any of the underlying [`reqwest::Client::get`] calls fail.
+    pub async fn comments_complete(
+        &self,
+        article: &ThingID,
+        options: CommentOptions,
+    ) -> crate::Result<CommentTree> {
+        let mut tree = self.comment_tree(article, options.clone()).await?;
+        self.expand_comments(&mut tree, article, options, DEFAULT_CONCURRENCY)
+            .await?;
+
+        Ok(tree)
+    }
+
+    /// [`Subreddit::expand_comments`] replaces the placeholders of `tree`, the comments on
+    /// `article`, with the comments they stand for, sending up to `concurrency` requests at
+    /// once, until there are none left.
+    ///
+    /// API Calls to: [`/api/morechildren`] and [`/r/{self.name}/comments/{article}.json`]
+    /// # Errors
+    /// Returns `Err` if any of the underlying [`reqwest::Client::get`] calls fail. `tree`
+    /// keeps the comments expanded so far, and the placeholders which weren't, so it can be
+    /// expanded again later.
+    pub async fn expand_comments(
+        &self,
+        tree: &mut CommentTree,
+        article: &ThingID,
+        options: CommentOptions,
+        concurrency: usize,
+    ) -> crate::Result<()> {
+        let link_id: std::sync::Arc<str> = format!("t3_{}", article.as_str()).into();
+        // Reddit sometimes sends back placeholders we've already expanded.
+        let mut requested = HashSet::new();
+
+        loop {
+            let mut ids = Vec::new();
+            let mut expansions = Vec::new();
+            // the placeholders this round expands, to put back if it fails.
+            let mut round = Vec::new();
+
+            for mut more in tree.take_more() {
+                if more.is_continue_thread() {
+                    if requested.insert(more.parent_id.to_string()) {
+                        expansions.push(Expansion::Thread(more.parent_id.as_str().to_string()));
+                        round.push(more);
+                    }
+                } else {
+                    more.children.retain(|id| requested.insert(id.clone()));
+                    if !more.children.is_empty() {
+                        ids.extend(more.children.iter().cloned());
+                        round.push(more);
+                    }
+                }
+            }
+            expansions.extend(
+                ids.chunks(MORECHILDREN_BATCH)
+                    .map(|batch| Expansion::Children(batch.to_vec())),
+            );
+
+            if expansions.is_empty() {
+                return Ok(());
+            }
+            debug!(requests = expansions.len(), "expanding comments");
+
+            let options = options.clone();
+            match self
+                .expand_round(expansions, article, &link_id, options, concurrency)
+                .await
+            {
+                Ok(nodes) => tree.splice(nodes),
+                Err(e) => {
+                    tree.splice(round.into_iter().map(CommentNode::More).collect());
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Sends the requests for `expansions`, up to `concurrency` at once, returning the nodes
+    /// they got back in the order Reddit sent them.
+    async fn expand_round(
+        &self,
+        expansions: Vec<Expansion>,
+        article: &ThingID,
+        link_id: &std::sync::Arc<str>,
+        options: CommentOptions,
+        concurrency: usize,
+    ) -> Result<Vec<CommentNode>> {
+        let mut pending = expansions.into_iter().enumerate();
+        let mut tasks = JoinSet::new();
+        let mut results = Vec::new();
+
+        loop {
+            while tasks.len() < concurrency.max(1) {
+                let Some((i, expansion)) = pending.next() else {
+                    break;
+                };
+
+                let this = self.clone();
+                let (article, link_id, options) =
+                    (article.clone(), link_id.clone(), options.clone());
+                tasks.spawn(async move {
+                    let res = match expansion {
+                        Expansion::Children(ids) => this.more_children(&link_id, &ids).await,
+                        Expansion::Thread(parent) => {
+                            this.continue_thread(&article, &parent, options).await
+                        }
+                    };
+                    (i, res)
+                });
+            }
+
+            let Some(joined) = tasks.join_next().await else {
+                break;
+            };
+            let (i, res) = joined.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()));
+            results.push((i, res?));
+        }
+
+        // keep Reddit's order, no matter which request finished first.
+        results.sort_by_key(|(i, _)| *i);
+        Ok(results.into_iter().flat_map(|(_, nodes)| nodes).collect())
+    }
+
+    /// Fetches the comments with the base36 identifiers `ids` on the post `link_id`.
+    async fn more_children(&self, link_id: &str, ids: &[String]) -> Result<Vec<CommentNode>> {
+        let path: PathBuf = ["api", "morechildren.json"].iter().collect();
+        let params = [
+            ("api_type", "json".to_string()),
+            ("link_id", link_id.to_string()),
+            ("children", ids.join(",")),
+            ("limit_children", "false".to_string()),
+        ];
+
+        let resp = self
+            .client
+            .get_json::<MoreChildren>(Scope::Read, &path, &params)
+            .await?;
+
+        if let Some(error) = resp.json.errors.into_iter().next() {
+            // Reddit answered with a 200, so there's no status to go by.
+            let url = build_url(self.client.base_url().clone(), &path, &params);
+            let context = Context::new(Method::GET, &url, StatusCode::OK)
+                .with_body(error.to_string().as_bytes());
+
+            let source = match serde_json::from_value::<JsonError>(error) {
+                Ok(error) => error.into(),
+                Err(e) => RedditError::Simple {
+                    message: e.to_string(),
+                },
+            };
+            return Err(Error::api(source, context));
+        }
+
+        resp.json
+            .data
+            .map(|data| data.things)
+            .ok_or_else(|| Error::unexpected("things", "nothing", &path))
+    }
+
+    /// Fetches the replies to the comment with the base36 identifier `parent` on `article`.
+    async fn continue_thread(
+        &self,
+        article: &ThingID,
+        parent: &str,
+        options: CommentOptions,
+    ) -> Result<Vec<CommentNode>> {
+        let path = self.comments_path(article);
+        let mut params: Vec<(&str, String)> = options.into();
+        params.push(("comment", parent.to_string()));
+
+        let thread = self
+            .client
+            .get_json::<Thread>(Scope::Read, &path, &params)
+            .await?;
+
+        Ok(CommentTree::from(thread)
+            .roots
+            .into_iter()
+            .filter_map(|node| match node {
+                CommentNode::Comment(comment) if comment.id == parent => Some(comment.replies),
+                _ => None,
+            })
+            .flatten()
+            .collect())
+    }
+}
+
+/// The replies to a [`Comment`], which Reddit sends as an empty string if there are none.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum Replies {
+    Listing { data: Listing<CommentNode> },
+    Empty(String),
+}
+
+/// Deserializes the `replies` of a [`Comment`].
+pub(crate) fn deserialize_replies<'de, D>(deserializer: D) -> Result<Vec<CommentNode>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde::Deserialize::deserialize(deserializer)? {
+        Replies::Listing { data } => Ok(data.children),
+        Replies::Empty(s) if s.is_empty() => Ok(Vec::new()),
+        Replies::Empty(s) => Err(serde::de::Error::invalid_value(
+            serde::de::Unexpected::Str(&s),
+            &"a listing or an empty string",
+        )),
+    }
+}
+
+/// What Reddit sends back for a post's comments: the post itself, and a listing of the
+/// top level comments.
+#[derive(serde::Deserialize)]
+pub(crate) struct Thread(serde::de::IgnoredAny, ThreadComments);
+
+#[derive(serde::Deserialize)]
+struct ThreadComments {
+    data: Listing<CommentNode>,
+}
+
+impl From<Thread> for CommentTree {
+    fn from(Thread(_, comments): Thread) -> Self {
+        Self {
+            roots: comments.data.children,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommentTree, Thread};
+    use crate::auth::Endpoints;
+    use crate::subreddit::feed::CommentOptions;
+    use crate::subreddit::submission::ThingID;
+    use crate::{Client, RetryPolicy};
+
+    const THREAD: &str = r#"[
+        {"kind": "Listing", "data": {"after": null, "before": null, "children": []}},
+        {"kind": "Listing", "data": {"after": null, "before": null, "children": [
+            {"kind": "t1", "data": {
+                "author": "a", "permalink": "/r/rust/comments/p/_/a/", "id": "a",
+                "name": "t1_a", "body": "first", "subreddit": "rust", "link_id": "t3_p",
+                "parent_id": "t3_p", "created_utc": 1686000000, "depth": 0, "score": 3,
+                "replies": {"kind": "Listing", "data": {"after": null, "before": null, "children": [
+                    {"kind": "t1", "data": {
+                        "author": "b", "permalink": "/r/rust/comments/p/_/b/", "id": "b",
+                        "name": "t1_b", "body": "reply", "subreddit": "rust",
+                        "link_id": "t3_p", "parent_id": "t1_a", "created_utc": 1686000000,
+                        "depth": 1, "score": 1, "replies": ""
+                    }},
+                    {"kind": "more", "data": {
+                        "id": "c", "name": "t1_c", "parent_id": "t1_a", "count": 2,
+                        "depth": 1, "children": ["c", "d"]
+                    }}
+                ]}}
+            }},
+            {"kind": "t1", "data": {
+                "author": "e", "permalink": "/r/rust/comments/p/_/e/", "id": "e",
+                "name": "t1_e", "body": "second", "subreddit": "rust", "link_id": "t3_p",
+                "parent_id": "t3_p", "created_utc": 1686000000, "depth": 0, "score": 0,
+                "replies": ""
+            }}
+        ]}}
+    ]"#;
+
+    fn tree() -> CommentTree {
+        serde_json::from_str::<Thread>(THREAD).unwrap().into()
+    }
+
+    #[test]
+    fn test_tree() {
+        let tree = tree();
+        assert_eq!(tree.roots.len(), 2);
+
+        let ids: Vec<_> = tree.comments().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "e"]);
+
+        let more: Vec<_> = tree.more().collect();
+        assert_eq!(more.len(), 1);
+        assert_eq!(more[0].children, ["c", "d"]);
+        assert!(!more[0].is_continue_thread());
+
+        let b = tree.find("b").unwrap();
+        assert_eq!(b.parent_id, ThingID::Comment("a".to_string()));
+        assert_eq!(b.link_id.to_string(), "t3_p");
+        assert_eq!(b.depth, Some(1));
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let tree = tree();
+
+        let ancestors = tree.ancestors("b").unwrap();
+        assert_eq!(ancestors.len(), 1);
+        assert_eq!(ancestors[0].id, "a");
+
+        assert!(tree.ancestors("e").unwrap().is_empty());
+        assert!(tree.ancestors("nope").is_none());
+    }
+
+    #[test]
+    fn test_splice() {
+        let mut tree = tree();
+        let more = tree.take_more();
+        assert_eq!(more.len(), 1);
+        assert_eq!(tree.more().count(), 0);
+
+        let json = r#"[
+            {"kind": "t1", "data": {
+                "author": "c", "permalink": "/r/rust/comments/p/_/c/", "id": "c",
+                "name": "t1_c", "subreddit": "rust", "link_id": "t3_p", "parent_id": "t1_a",
+                "created_utc": 1686000000, "replies": ""
+            }},
+            {"kind": "t1", "data": {
+                "author": "f", "permalink": "/r/rust/comments/p/_/f/", "id": "f",
+                "name": "t1_f", "subreddit": "rust", "link_id": "t3_p", "parent_id": "t1_c",
+                "created_utc": 1686000000, "replies": ""
+            }},
+            {"kind": "t1", "data": {
+                "author": "d", "permalink": "/r/rust/comments/p/_/d/", "id": "d",
+                "name": "t1_d", "subreddit": "rust", "link_id": "t3_p", "parent_id": "t1_a",
+                "created_utc": 1686000000, "replies": ""
+            }},
+            {"kind": "t1", "data": {
+                "author": "g", "permalink": "/r/rust/comments/p/_/g/", "id": "g",
+                "name": "t1_g", "subreddit": "rust", "link_id": "t3_p", "parent_id": "t3_p",
+                "created_utc": 1686000000, "replies": ""
+            }}
+        ]"#;
+        tree.splice(serde_json::from_str(json).unwrap());
+
+        let ids: Vec<_> = tree.comments().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c", "f", "d", "e", "g"]);
+
+        let ancestors: Vec<_> = tree
+            .ancestors("f")
+            .unwrap()
+            .iter()
+            .map(|c| c.id.as_str())
+            .collect();
+        assert_eq!(ancestors, ["c", "a"]);
+    }
+
+    #[test]
+    fn test_flatten() {
+        let comments = tree().flatten();
+        let ids: Vec<_> = comments.iter().map(|c| c.id.as_str()).collect();
+
+        assert_eq!(ids, ["a", "b", "e"]);
+        assert!(comments.iter().all(|c| c.replies.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_expand_failure() {
+        // nothing listens there, so every request fails.
+        let local = url::Url::parse("http://127.0.0.1:9/").unwrap();
+        let client = Client::new("test")
+            .with_endpoints(Endpoints::new().api(local))
+            .with_retry_policy(RetryPolicy::never());
+
+        let mut tree = tree();
+        let article = ThingID::Link("p".to_string());
+        let res = client
+            .subreddit("rust")
+            .expand_comments(&mut tree, &article, CommentOptions::default(), 2)
+            .await;
+        assert!(res.is_err());
+
+        // the placeholder is back under its parent, so we can try again.
+        let replies = tree.roots[0].replies();
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[1].as_more().unwrap().children, ["c", "d"]);
+        assert_eq!(tree.comments().count(), 3);
+    }
+}
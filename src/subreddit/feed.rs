@@ -13,6 +13,10 @@ pub struct Options {
     count: u64,
 
     /// The number of items that can be in this listing.
+    ///
+    /// Reddit sends at most 100 items per request, so anything above that is fetched with
+    /// multiple requests, up to the [`crate::pagination::LISTING_HORIZON`]. A limit of 0
+    /// fetches nothing.
     limit: u64,
 }
 
@@ -148,6 +152,7 @@ impl Options {
         self
     }
 
+    /// Starts at `before` and pages backwards from there, towards newer items.
     #[must_use]
     pub fn before(mut self, before: &str) -> Self {
         self.before = Some(Arc::from(before));
@@ -167,6 +172,36 @@ impl Options {
     }
 }
 
+impl Options {
+    /// Returns how many items a page has at most.
+    pub(crate) const fn page_size(&self) -> u64 {
+        self.limit
+    }
+
+    /// Returns how many items of the listing come before the page.
+    pub(crate) const fn offset(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns `true` if we're paging backwards from [`Options::before`].
+    pub(crate) const fn backwards(&self) -> bool {
+        self.before.is_some()
+    }
+
+    /// Moves these [`Options`] past a page of `seen` items, which starts at `before` and
+    /// ends at `after`, in whichever direction we're paging.
+    pub(crate) fn advance(&mut self, seen: u64, after: Option<&str>, before: Option<&str>) {
+        if self.backwards() {
+            self.count = self.count.saturating_sub(seen);
+            self.before = before.map(Arc::from);
+            self.after = None;
+        } else {
+            self.count += seen;
+            self.after = after.map(Arc::from);
+        }
+    }
+}
+
 impl From<Options> for Vec<(&str, String)> {
     fn from(value: Options) -> Self {
         let mut params = Vec::with_capacity(4);
@@ -247,4 +282,37 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_advance() {
+        let mut opts = Options::new().count(3).after("xd");
+        opts.advance(25, Some("t3_abc"), Some("t3_xyz"));
+        let params: Vec<(&str, String)> = opts.into();
+
+        assert_eq!(
+            params,
+            vec![
+                ("count", "28".to_string()),
+                ("limit", "100".to_string()),
+                ("after", "t3_abc".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_advance_backwards() {
+        let mut opts = Options::new().count(300).before("t3_x");
+        opts.advance(100, Some("t3_abc"), Some("t3_xyz"));
+        let params: Vec<(&str, String)> = opts.clone().into();
+
+        // we keep going away from `t3_x`, instead of turning back across it.
+        assert_eq!(
+            params,
+            vec![
+                ("count", "200".to_string()),
+                ("limit", "100".to_string()),
+                ("before", "t3_xyz".to_string()),
+            ]
+        );
+    }
 }
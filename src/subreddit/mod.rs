@@ -1,3 +1,4 @@
+pub mod comments;
 pub mod feed;
 #[cfg(feature = "stream")]
 #[doc(cfg(feature = "stream"))]
@@ -12,26 +13,26 @@ use std::sync::Arc;
 
 #[cfg(feature = "stream")]
 use self::multistream::{Storage, StreamState};
-use crate::auth::Authenticator;
-use crate::Client;
+use crate::auth::{Authenticator, Scope};
+use crate::{Body, Client};
 use feed::CommentOptions;
 #[cfg(feature = "stream")]
 use futures_util::Stream;
+use reqwest::Method;
+use serde::de::IgnoredAny;
 #[cfg(feature = "stream")]
 use tokio::time::Interval;
 
+use self::comments::{CommentTree, Thread};
 use self::submission::Submission;
 use self::submission::Submissions;
-use crate::response::Generic;
+use crate::pagination::{self, Page, Paginator, Shape};
 use submission::{Comment, ThingID};
 
 #[cfg(feature = "stream")]
 #[doc(cfg(feature = "stream"))]
 pub use self::multistream::StreamBuilder;
 
-type FeedResponse = Generic<Submission>;
-type CommentsResponse = Generic<Comment>;
-
 #[derive(Clone)]
 pub struct Subreddit<A: Authenticator> {
     pub(crate) client: Client<A>,
@@ -58,7 +59,48 @@ where
     pub async fn about(&self) -> crate::Result<HashMap<String, serde_json::Value>> {
         let path: PathBuf = ["r", &self.name, "about.json"].iter().collect();
 
-        self.client.get_json(&path, &[]).await
+        self.client
+            .get_subreddit_json(Scope::Read, &path, &[])
+            .await
+    }
+
+    /// [`Subreddit::quarantine_opt_in`] lets the account this [`Subreddit`]'s [`Client`] is
+    /// logged in to read it, if it's quarantined.
+    ///
+    /// Until then, reading it fails with [`crate::errors::RedditError::SubredditQuarantined`].
+    /// If the [`Client`] belongs to a [`crate::ClientPool`], every member's account opts in,
+    /// one after the other.
+    ///
+    /// API Calls to: [`/api/quarantine_optin`]
+    /// # Errors
+    /// Returns [`crate::auth::Error::MissingScope`] if the [`Client`] isn't logged in to an
+    /// account, or wasn't granted [`Scope::Account`].
+    ///
+    /// If it belongs to a [`crate::ClientPool`] and some members fail, the others still opt
+    /// in, and it returns [`crate::Error::Pool`] saying which ones did.
+    pub async fn quarantine_opt_in(&self) -> crate::Result<()> {
+        self.quarantine("quarantine_optin").await
+    }
+
+    /// [`Subreddit::quarantine_opt_out`] undoes [`Subreddit::quarantine_opt_in`].
+    ///
+    /// API Calls to: [`/api/quarantine_optout`]
+    /// # Errors
+    /// Returns [`crate::auth::Error::MissingScope`] if the [`Client`] isn't logged in to an
+    /// account, or wasn't granted [`Scope::Account`].
+    pub async fn quarantine_opt_out(&self) -> crate::Result<()> {
+        self.quarantine("quarantine_optout").await
+    }
+
+    async fn quarantine(&self, endpoint: &str) -> crate::Result<()> {
+        let path: PathBuf = ["api", endpoint].iter().collect();
+        let form = [("sr_name", &*self.name)];
+
+        self.client
+            .write_json::<IgnoredAny>(Method::POST, Scope::Account, &path, &Body::Form(&form))
+            .await?;
+
+        Ok(())
     }
 
     /// [`Subreddit::feed_with_options`] returns submissions sorted by [`Sort`] with [`Options`] on this [`Subreddit`]
@@ -71,24 +113,51 @@ where
         sort: Sort,
         options: Options,
     ) -> crate::Result<Submissions> {
-        let path: PathBuf = ["r", &self.name, sort.as_str(), ".json"].iter().collect();
-        let mut params: Vec<(&str, String)> = options.into();
+        Ok(self.feed_page(sort, options).await?.items)
+    }
+
+    /// [`Subreddit::feed_page`] returns a [`Page`] of submissions sorted by [`Sort`] with
+    /// [`Options`] on this [`Subreddit`], along with the cursors to the pages around it.
+    ///
+    /// API Calls to: [`/r/{self.name}/{sort}.json`]
+    /// # Errors
+    /// Returns `Err` if the underlying [`reqwest::Client::get`] call fails.
+    pub async fn feed_page(&self, sort: Sort, options: Options) -> crate::Result<Page<Submission>> {
+        let (path, params) = Self::feed_request(&self.name, sort);
+
+        pagination::fetch_listing(&self.client, &path, &params, options, "link").await
+    }
+
+    /// [`Subreddit::paginate`] returns a [`Paginator`] over the submissions sorted by
+    /// [`Sort`] on this [`Subreddit`], starting at [`Options`].
+    ///
+    /// API Calls to: [`/r/{self.name}/{sort}.json`]
+    pub fn paginate(&self, sort: Sort, options: Options) -> Paginator<A, Submission>
+    where
+        A: 'static,
+    {
+        let (path, params) = Self::feed_request(&self.name, sort);
+
+        Paginator::new(
+            self.client.clone(),
+            path,
+            params,
+            options,
+            Shape::Listing { kind: "link" },
+        )
+    }
+
+    /// Returns the path and parameters of a feed sorted by `sort`, besides its [`Options`].
+    fn feed_request(name: &str, sort: Sort) -> (PathBuf, Vec<(&'static str, String)>) {
+        let path: PathBuf = ["r", name, sort.as_str(), ".json"].iter().collect();
+        let mut params = Vec::new();
 
         match sort {
             Sort::Top(tp) | Sort::Controversial(tp) => params.push(("t", tp.as_str().to_string())),
             _ => (),
         }
 
-        match self.client.get_json::<FeedResponse>(&path, &params).await? {
-            Generic::Listing { data } => Ok(data
-                .into_iter()
-                .map(|c| match c {
-                    Generic::Link { data } => data,
-                    other => unimplemented!("expected Listing but got {}", other.kind_name()),
-                })
-                .collect()),
-            other => unimplemented!("expected Listing but got {}", other.kind_name()),
-        }
+        (path, params)
     }
 
     /// [`Subreddit::feed`] returns submissions sorted by [`Sort`] on this [`Subreddit`]
@@ -118,43 +187,81 @@ where
         self.feed(Sort::Hot).await
     }
 
-    /// [`Subreddit::comments`] returns submissions sorted by [`Sort::Hot`] on this [`Subreddit`]
+    /// [`Subreddit::comments`] returns the top level comments on `article`, with their
+    /// replies.
     ///
-    /// API Calls to: [`/r/{self.name}/hot.json`]
-    /// # Errors
-    /// Returns `Err` if the underlying [`reqwest::Client::get`] call fails.
+    /// Top level placeholders for the comments Reddit left out are dropped, so on big
+    /// threads this is only some of the comments. Use [`Subreddit::comment_tree`] to keep
+    /// the placeholders, or [`Subreddit::comments_complete`] to fetch every comment.
     ///
-    /// # Panics
-    /// This function cannot panic.
+    /// API Calls to: [`/r/{self.name}/comments/{article}.json`]
+    /// # Errors
+    /// Returns `Err` if the underlying [`reqwest::Client::get`] call fails, or Reddit sends
+    /// something other than the post and its comments.
     pub async fn comments(
         &self,
         article: &ThingID,
         options: CommentOptions,
     ) -> crate::Result<Vec<Comment>> {
-        let path: PathBuf = ["r", &self.name, "comments", article.as_str(), ".json"]
-            .iter()
-            .collect();
+        let path = self.comments_path(article);
+        let params: Vec<(&str, String)> = options.into();
+
+        let page = pagination::fetch_page(&self.client, &path, &params, Shape::Thread).await?;
+        Ok(page.items)
+    }
+
+    /// [`Subreddit::comment_tree`] returns the comments on `article` as a [`CommentTree`],
+    /// with their replies and placeholders for the comments Reddit left out.
+    ///
+    /// API Calls to: [`/r/{self.name}/comments/{article}.json`]
+    /// # Errors
+    /// Returns `Err` if the underlying [`reqwest::Client::get`] call fails.
+    pub async fn comment_tree(
+        &self,
+        article: &ThingID,
+        options: CommentOptions,
+    ) -> crate::Result<CommentTree> {
+        let path = self.comments_path(article);
         let params: Vec<(&str, String)> = options.into();
 
-        let listings = self
+        let thread = self
             .client
-            .get_json::<Vec<CommentsResponse>>(&path, &params)
+            .get_json::<Thread>(Scope::Read, &path, &params)
             .await?;
 
-        // the post itself + the comments
-        assert_eq!(listings.len(), 2);
-
-        match listings.into_iter().nth(1) {
-            Some(Generic::Listing { data }) => Ok(data
-                .into_iter()
-                .map(|c| match c {
-                    Generic::Comment { data } => data,
-                    other => unimplemented!("expected Comment but got {}", other.kind_name()),
-                })
-                .collect()),
-            Some(other) => unimplemented!("expected Listing but got {}", other.kind_name()),
-            None => unreachable!("got nothing"),
-        }
+        Ok(thread.into())
+    }
+
+    /// [`Subreddit::paginate_comments`] returns a [`Paginator`] over the comments on
+    /// `article`.
+    ///
+    /// Reddit sends a post's comments in one go, so there's only ever one [`Page`]. Like
+    /// [`Subreddit::comments`], it drops the top level placeholders for the comments Reddit
+    /// left out, which [`Page::omitted`] counts. Use [`Subreddit::comments_complete`] to
+    /// fetch every comment.
+    ///
+    /// API Calls to: [`/r/{self.name}/comments/{article}.json`]
+    pub fn paginate_comments(
+        &self,
+        article: &ThingID,
+        options: CommentOptions,
+    ) -> Paginator<A, Comment>
+    where
+        A: 'static,
+    {
+        Paginator::new(
+            self.client.clone(),
+            self.comments_path(article),
+            options.into(),
+            Options::default(),
+            Shape::Thread,
+        )
+    }
+
+    fn comments_path(&self, article: &ThingID) -> PathBuf {
+        ["r", &self.name, "comments", article.as_str(), ".json"]
+            .iter()
+            .collect()
     }
 
     /// Creates a new [`Stream`] of [`Submission`].
@@ -272,7 +379,7 @@ mod tests_stream {
 mod tests {
     use dotenv::{dotenv, var};
 
-    use crate::errors::Error;
+    use crate::errors::RedditError;
     use crate::subreddit::feed::{self, CommentOptions};
     use crate::Client;
 
@@ -303,7 +410,10 @@ mod tests {
         let about = sub.about().await;
         assert!(about.is_err());
         let about = about.unwrap_err();
-        assert!(matches!(about, Error::Reddit(..)));
+        assert!(matches!(
+            about.reddit_error(),
+            Some(RedditError::NotFound | RedditError::SubredditBanned)
+        ));
     }
 
     #[tokio::test]
@@ -328,6 +438,5 @@ mod tests {
             .await;
         eprintln!("{comments:#?}");
         assert!(comments.is_ok());
-        let comments = comments.unwrap();
     }
 }
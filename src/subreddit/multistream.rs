@@ -32,7 +32,7 @@ pub enum Error {
 ///
 /// # fn main {
 ///    let mut stream = StreamBuilder::new()
-///        .poll_period(Duration::from_secs(60))
+///        .poll_period(Duration::from_mins(1))
 ///        .add_sub(client.subreddit("kpop"))
 ///        .skip_initial(false)
 ///        .build(0..0)
@@ -291,7 +291,7 @@ mod tests {
     async fn test_subreddit_missing() {
         let b = StreamBuilder::<Anon, _>::new()
             .skip_initial(false)
-            .poll_period(Duration::from_secs(60))
+            .poll_period(Duration::from_mins(1))
             .set_storage(SetStorage::new())
             .build(0..0);
 
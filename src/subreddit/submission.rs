@@ -3,7 +3,10 @@ use std::collections::HashMap;
 use serde::de;
 use url::Url;
 
+use crate::pagination::Thing;
 use crate::response::RedditUrl;
+use crate::subreddit::comments::{deserialize_replies, CommentNode};
+use crate::Timestamp;
 
 /// [`GalleryItem`] contains the data of an item in a Reddit gallery.
 #[derive(Debug, Clone, serde::Deserialize)]
@@ -96,6 +99,8 @@ pub struct Media {
 }
 
 /// Represents a single [`Submission`].
+// these are the flags Reddit sends.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct Submission {
     /// The author of this post.
@@ -117,22 +122,132 @@ pub struct Submission {
     pub url: Option<RedditUrl>,
     /// The title of the post.
     pub title: String,
-    /// The submission text-body.
+    /// The submission text-body, if this is a self post with any.
+    #[serde(rename = "selftext", default, deserialize_with = "non_empty")]
     pub body: Option<String>,
+    /// The submission text-body, rendered as HTML.
+    #[serde(rename = "selftext_html")]
+    pub body_html: Option<String>,
     /// The subreddit that this submission was posted in (not including `/r/`)
     pub subreddit: String,
+    /// The fullname of the subreddit that this submission was posted in.
+    pub subreddit_id: Option<String>,
+    /// The domain this post links to, or `self.{subreddit}` for self posts.
+    #[serde(default)]
+    pub domain: String,
+    /// The score of this post, upvotes minus downvotes.
+    #[serde(default)]
+    pub score: i64,
+    /// The share of votes which are upvotes, from 0 to 1.
+    #[serde(default)]
+    pub upvote_ratio: f64,
+    /// The number of comments on this post.
+    #[serde(default)]
+    pub num_comments: u64,
+    /// When this post was created.
+    pub created_utc: Timestamp,
+    /// Whether, and when, this post was last edited.
+    #[serde(default)]
+    pub edited: Edited,
+    /// Whether this post is marked NSFW.
+    #[serde(default)]
+    pub over_18: bool,
+    /// Whether this post is marked as a spoiler.
+    #[serde(default)]
+    pub spoiler: bool,
+    /// Whether this post is pinned to the top of the subreddit.
+    #[serde(default)]
+    pub stickied: bool,
+    /// Whether this post is locked, so no one can comment on it.
+    #[serde(default)]
+    pub locked: bool,
+    /// Whether this post is archived, so no one can vote or comment on it.
+    #[serde(default)]
+    pub archived: bool,
+    /// Whether this is a self post, rather than a link.
+    #[serde(default)]
+    pub is_self: bool,
+    /// Whether this post is a video uploaded to Reddit.
+    #[serde(default)]
+    pub is_video: bool,
+    /// The text of this post's flair.
+    pub link_flair_text: Option<String>,
+    /// The text of the author's flair.
+    pub author_flair_text: Option<String>,
+    /// Whether the author posted this as a moderator or an admin.
+    pub distinguished: Option<Distinguished>,
     /// The items of a gallery.
     pub gallery_data: Option<Gallery>,
     /// The media metadata.
     pub media_metadata: Option<HashMap<String, MediaStatus>>,
     /// This post's media.
     pub media: Option<Media>,
-    pub crosspost_parent_list: Option<Vec<Submission>>,
+    pub crosspost_parent_list: Option<Vec<Self>>,
     /// The rest of the attributes as a [`HashMap`].
     #[serde(flatten)]
     pub rest: HashMap<String, serde_json::Value>,
 }
 
+/// Whether a [`Submission`] or a [`Comment`] was edited.
+///
+/// Reddit sends `false` for things which weren't, and when they were edited otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(untagged)]
+pub enum Edited {
+    /// Whether it was edited, for things Reddit doesn't know when they were.
+    Flag(bool),
+    /// When it was last edited.
+    At(Timestamp),
+}
+
+impl Default for Edited {
+    fn default() -> Self {
+        Self::Flag(false)
+    }
+}
+
+impl Edited {
+    /// Returns `true` if it was edited.
+    #[must_use]
+    pub const fn is_edited(&self) -> bool {
+        match self {
+            Self::Flag(edited) => *edited,
+            Self::At(_) => true,
+        }
+    }
+
+    /// Returns when it was last edited, if Reddit knows.
+    #[must_use]
+    pub const fn at(&self) -> Option<Timestamp> {
+        match self {
+            Self::Flag(_) => None,
+            Self::At(at) => Some(*at),
+        }
+    }
+}
+
+/// Why a [`Submission`] or a [`Comment`] is distinguished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Distinguished {
+    /// Posted by a moderator, as a moderator.
+    Moderator,
+    /// Posted by a Reddit admin, as an admin.
+    Admin,
+    /// Distinguished some other way, e.g. by Reddit for special users.
+    #[serde(other)]
+    Special,
+}
+
+/// Deserializes an optional string, which Reddit sends as `""` when there's nothing.
+fn non_empty<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s: Option<String> = de::Deserialize::deserialize(deserializer)?;
+    Ok(s.filter(|s| !s.is_empty()))
+}
+
 /// Represents a single [`Comment`].
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct Comment {
@@ -151,17 +266,64 @@ pub struct Comment {
     /// - t6_ - Award
     /// - t8_ - ``PromoCampaign``
     pub name: ThingID,
-    /// The submission text-body.
+    /// The comment text-body.
     pub body: Option<String>,
-    /// The subreddit that this submission was posted in (not including `/r/`)
+    /// The comment text-body, rendered as HTML.
+    pub body_html: Option<String>,
+    /// The subreddit that this comment was posted in (not including `/r/`)
     pub subreddit: String,
+    /// The comment or post this one replies to.
+    pub parent_id: ThingID,
+    /// The post this comment is on.
+    pub link_id: ThingID,
+    /// How deep in the thread this comment is, starting at 0 for top level ones.
+    pub depth: Option<u64>,
+    /// The score of this comment, upvotes minus downvotes.
+    #[serde(default)]
+    pub score: i64,
+    /// Whether this comment got about as many downvotes as upvotes, `1` if it did.
+    #[serde(default)]
+    pub controversiality: u8,
+    /// When this comment was created.
+    pub created_utc: Timestamp,
+    /// Whether, and when, this comment was last edited.
+    #[serde(default)]
+    pub edited: Edited,
+    /// Whether the author of this comment also posted the post it's on.
+    #[serde(default)]
+    pub is_submitter: bool,
+    /// Whether this comment is pinned to the top of the thread.
+    #[serde(default)]
+    pub stickied: bool,
+    /// Whether this comment is collapsed by default, e.g. because of its score.
+    #[serde(default)]
+    pub collapsed: bool,
+    /// Whether the author posted this as a moderator or an admin.
+    pub distinguished: Option<Distinguished>,
+    /// The text of the author's flair.
+    pub author_flair_text: Option<String>,
+    /// The replies to this comment.
+    #[serde(default, deserialize_with = "deserialize_replies")]
+    pub replies: Vec<CommentNode>,
     /// The rest of the attributes as a [`HashMap`].
     #[serde(flatten)]
     pub rest: HashMap<String, serde_json::Value>,
 }
 
+impl Thing for Submission {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Thing for Comment {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
 /// Represents a [`ThingID`].
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ThingID {
     Comment(String),
     Account(String),
@@ -186,6 +348,29 @@ impl ThingID {
     }
 }
 
+impl ThingID {
+    /// Returns the kind prefix of this [`ThingID`], e.g. `t1` for a comment.
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Comment(_) => "t1",
+            Self::Account(_) => "t2",
+            Self::Link(_) => "t3",
+            Self::Message(_) => "t4",
+            Self::Subreddit(_) => "t5",
+            Self::Award(_) => "t6",
+            Self::PromoCampaign(_) => "t8",
+        }
+    }
+}
+
+/// Formats the fullname, e.g. `t1_2qpqw`.
+impl std::fmt::Display for ThingID {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}_{}", self.kind(), self.as_str())
+    }
+}
+
 impl<'de> de::Deserialize<'de> for ThingID {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -208,8 +393,10 @@ impl<'de> de::Deserialize<'de> for ThingID {
                 E: de::Error,
             {
                 // t1_12345
-                let thing = &v[0..2];
-                let id = v[3..].to_string();
+                let Some((thing, id)) = v.split_once('_') else {
+                    return Err(de::Error::invalid_value(de::Unexpected::Str(v), &self));
+                };
+                let id = id.to_string();
 
                 match thing {
                     "t1" => Ok(ThingID::Comment(id)),
@@ -230,3 +417,63 @@ impl<'de> de::Deserialize<'de> for ThingID {
 
 /// Represents multiple [`Submission`]s.
 pub type Submissions = Vec<Submission>;
+
+#[cfg(test)]
+mod tests {
+    use super::{Distinguished, Edited, Submission, ThingID};
+    use crate::Timestamp;
+
+    fn submission(extra: &str) -> Submission {
+        let json = format!(
+            r#"{{
+                "author": "a", "permalink": "/r/rust/comments/p/title/", "id": "p",
+                "name": "t3_p", "url": "https://www.reddit.com/r/rust/comments/p/title/",
+                "title": "title", "subreddit": "rust", "score": 42, "upvote_ratio": 0.97,
+                "num_comments": 7, "created_utc": 1686000000.0, "over_18": false,
+                "is_self": true, "link_flair_text": null, "distinguished": "moderator",
+                "some_new_field": 1{extra}
+            }}"#
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_submission() {
+        let post = submission(
+            r#", "selftext": "hello", "selftext_html": "<p>hello</p>", "edited": false"#,
+        );
+
+        assert_eq!(post.body.as_deref(), Some("hello"));
+        assert_eq!(post.body_html.as_deref(), Some("<p>hello</p>"));
+        assert_eq!(post.score, 42);
+        assert_eq!(post.num_comments, 7);
+        assert!(post.is_self);
+        assert_eq!(post.distinguished, Some(Distinguished::Moderator));
+        assert!(!post.edited.is_edited());
+
+        // only the keys we don't know about end up in `rest`.
+        assert_eq!(post.rest.len(), 1);
+        assert!(post.rest.contains_key("some_new_field"));
+    }
+
+    #[test]
+    fn test_edited() {
+        let post = submission(r#", "selftext": "", "edited": 1686000100.0"#);
+
+        assert!(post.body.is_none());
+        let edited = Timestamp::from_unix(1_686_000_100);
+        assert_eq!(post.edited, Edited::At(edited));
+        assert_eq!(post.edited.at(), Some(edited));
+        assert!(edited > post.created_utc);
+    }
+
+    #[test]
+    fn test_thing_id() {
+        let id: ThingID = serde_json::from_str(r#""t1_2qpqw""#).unwrap();
+        assert_eq!(id, ThingID::Comment("2qpqw".to_string()));
+        assert_eq!(id.to_string(), "t1_2qpqw");
+
+        assert!(serde_json::from_str::<ThingID>(r#""t9_2qpqw""#).is_err());
+        assert!(serde_json::from_str::<ThingID>(r#""x""#).is_err());
+    }
+}
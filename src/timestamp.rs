@@ -0,0 +1,253 @@
+use std::ops::{Add, Sub};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A point in time, as Reddit sends them: seconds since the Unix epoch, in UTC.
+///
+/// Kept with millisecond precision, so [`Timestamp`]s can be compared, sorted and hashed.
+/// With the `chrono` or `time` features enabled, they convert to and from their date-time
+/// types.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    millis: i64,
+}
+
+/// A [`Timestamp`] too far from the Unix epoch to be represented.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("timestamp is out of range")]
+pub struct OutOfRange;
+
+impl Timestamp {
+    /// The Unix epoch, 1970-01-01 00:00:00 UTC.
+    pub const UNIX_EPOCH: Self = Self { millis: 0 };
+
+    /// Creates a [`Timestamp`] `secs` seconds after the Unix epoch.
+    #[must_use]
+    pub const fn from_unix(secs: i64) -> Self {
+        Self {
+            millis: secs.saturating_mul(1000),
+        }
+    }
+
+    /// Creates a [`Timestamp`] `millis` milliseconds after the Unix epoch.
+    #[must_use]
+    pub const fn from_unix_millis(millis: i64) -> Self {
+        Self { millis }
+    }
+
+    /// Creates a [`Timestamp`] `secs` seconds after the Unix epoch, rounded to the nearest
+    /// millisecond, or `None` if it's not a number.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn from_unix_f64(secs: f64) -> Option<Self> {
+        // the cast saturates, which is as close as we can get anyway.
+        secs.is_finite().then(|| Self {
+            millis: (secs * 1000.0).round() as i64,
+        })
+    }
+
+    /// Returns the current time.
+    #[must_use]
+    pub fn now() -> Self {
+        SystemTime::now().into()
+    }
+
+    /// Returns the whole seconds since the Unix epoch.
+    #[must_use]
+    pub const fn as_unix(&self) -> i64 {
+        self.millis.div_euclid(1000)
+    }
+
+    /// Returns the milliseconds since the Unix epoch.
+    #[must_use]
+    pub const fn as_unix_millis(&self) -> i64 {
+        self.millis
+    }
+
+    /// Returns the seconds since the Unix epoch, like Reddit sends them.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn as_unix_f64(&self) -> f64 {
+        self.millis as f64 / 1000.0
+    }
+
+    /// Returns how long after `earlier` this is, or `None` if it's before it.
+    #[must_use]
+    pub fn duration_since(&self, earlier: Self) -> Option<Duration> {
+        let millis = self.millis.checked_sub(earlier.millis)?;
+        u64::try_from(millis).ok().map(Duration::from_millis)
+    }
+
+    /// Returns how long ago this was, or zero if it's in the future.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        Self::now().duration_since(*self).unwrap_or_default()
+    }
+}
+
+/// Returns `duration` in milliseconds, saturating at [`i64::MAX`].
+fn millis(duration: Duration) -> i64 {
+    i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+}
+
+impl Add<Duration> for Timestamp {
+    type Output = Self;
+
+    fn add(self, rhs: Duration) -> Self {
+        Self {
+            millis: self.millis.saturating_add(millis(rhs)),
+        }
+    }
+}
+
+impl Sub<Duration> for Timestamp {
+    type Output = Self;
+
+    fn sub(self, rhs: Duration) -> Self {
+        Self {
+            millis: self.millis.saturating_sub(millis(rhs)),
+        }
+    }
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(value: SystemTime) -> Self {
+        match value.duration_since(UNIX_EPOCH) {
+            Ok(after) => Self::UNIX_EPOCH + after,
+            Err(before) => Self::UNIX_EPOCH - before.duration(),
+        }
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(value: Timestamp) -> Self {
+        let millis = Duration::from_millis(value.millis.unsigned_abs());
+
+        if value.millis >= 0 {
+            UNIX_EPOCH + millis
+        } else {
+            UNIX_EPOCH - millis
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Timestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let secs = f64::deserialize(deserializer)?;
+
+        Self::from_unix_f64(secs).ok_or_else(|| {
+            serde::de::Error::invalid_value(
+                serde::de::Unexpected::Float(secs),
+                &"seconds since the Unix epoch",
+            )
+        })
+    }
+}
+
+impl serde::Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_f64(self.as_unix_f64())
+    }
+}
+
+#[cfg(feature = "chrono")]
+#[doc(cfg(feature = "chrono"))]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for Timestamp {
+    fn from(value: chrono::DateTime<Tz>) -> Self {
+        Self::from_unix_millis(value.timestamp_millis())
+    }
+}
+
+#[cfg(feature = "chrono")]
+#[doc(cfg(feature = "chrono"))]
+impl TryFrom<Timestamp> for chrono::DateTime<chrono::Utc> {
+    type Error = OutOfRange;
+
+    fn try_from(value: Timestamp) -> Result<Self, Self::Error> {
+        Self::from_timestamp_millis(value.millis).ok_or(OutOfRange)
+    }
+}
+
+#[cfg(feature = "time")]
+#[doc(cfg(feature = "time"))]
+impl From<time::OffsetDateTime> for Timestamp {
+    fn from(value: time::OffsetDateTime) -> Self {
+        // `time` only goes up to the year 9999, so this always fits.
+        let millis = value.unix_timestamp_nanos() / 1_000_000;
+        Self::from_unix_millis(i64::try_from(millis).unwrap_or(i64::MAX))
+    }
+}
+
+#[cfg(feature = "time")]
+#[doc(cfg(feature = "time"))]
+impl TryFrom<Timestamp> for time::OffsetDateTime {
+    type Error = OutOfRange;
+
+    fn try_from(value: Timestamp) -> Result<Self, Self::Error> {
+        Self::from_unix_timestamp_nanos(i128::from(value.millis) * 1_000_000)
+            .map_err(|_| OutOfRange)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use super::Timestamp;
+
+    #[test]
+    fn test_timestamp() {
+        let ts: Timestamp = serde_json::from_str("1686000000.5").unwrap();
+        assert_eq!(ts.as_unix(), 1_686_000_000);
+        assert_eq!(ts.as_unix_millis(), 1_686_000_000_500);
+        assert_eq!(serde_json::to_string(&ts).unwrap(), "1686000000.5");
+
+        let whole: Timestamp = serde_json::from_str("1686000000").unwrap();
+        assert_eq!(whole, Timestamp::from_unix(1_686_000_000));
+        assert!(whole < ts);
+        assert_eq!(ts.duration_since(whole), Some(Duration::from_millis(500)));
+        assert_eq!(whole.duration_since(ts), None);
+
+        let later = whole + Duration::from_mins(1);
+        assert_eq!(later - Duration::from_mins(1), whole);
+    }
+
+    #[test]
+    fn test_system_time() {
+        let now = SystemTime::now();
+        let ts = Timestamp::from(now);
+        let back = SystemTime::from(ts);
+
+        assert!(now.duration_since(back).unwrap() < Duration::from_millis(1));
+
+        let before = UNIX_EPOCH - Duration::from_secs(10);
+        assert_eq!(Timestamp::from(before), Timestamp::from_unix(-10));
+        assert_eq!(SystemTime::from(Timestamp::from_unix(-10)), before);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono() {
+        let ts = Timestamp::from_unix_millis(1_686_000_000_500);
+        let dt = chrono::DateTime::<chrono::Utc>::try_from(ts).unwrap();
+
+        assert_eq!(dt.timestamp_millis(), 1_686_000_000_500);
+        assert_eq!(Timestamp::from(dt), ts);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time() {
+        let ts = Timestamp::from_unix_millis(1_686_000_000_500);
+        let dt = time::OffsetDateTime::try_from(ts).unwrap();
+
+        assert_eq!(dt.unix_timestamp(), 1_686_000_000);
+        assert_eq!(Timestamp::from(dt), ts);
+        assert!(time::OffsetDateTime::try_from(Timestamp::from_unix_millis(i64::MAX)).is_err());
+    }
+}